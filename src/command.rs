@@ -0,0 +1,124 @@
+use super::*;
+use shipyard::*;
+
+// Hierarchy edits as values, for undo/redo
+//
+// `apply` returns the command that undoes it, e.g.
+//
+// let undo = HierarchyCommand::Remove { root }.apply(&mut hierarchy);
+// let redo = undo.apply(&mut hierarchy);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HierarchyCommand {
+    // append `id` as the last child of `parent`
+    Attach {
+        id: EntityId,
+        parent: EntityId,
+    },
+    // take `id` (and its subtree) out of its parent, leaving it as a root
+    Detach {
+        id: EntityId,
+    },
+    // attach `id` as the `index`th child of `parent`
+    Move {
+        id: EntityId,
+        parent: EntityId,
+        index: usize,
+    },
+    // reorder the children of `parent` to follow `children`
+    Reorder {
+        parent: EntityId,
+        children: Vec<EntityId>,
+    },
+    // remove the subtree under `root` from the hierarchy
+    Remove {
+        root: EntityId,
+    },
+    // undo of Remove: put `root` back at `location` (if it had a parent)
+    // and re-attach the (child, parent) pairs of `descendants` in order
+    Restore {
+        root: EntityId,
+        location: Option<(EntityId, usize)>,
+        descendants: Vec<(EntityId, EntityId)>,
+    },
+}
+
+impl HierarchyCommand {
+    // The command which, applied right now, would undo this one
+    pub fn invert<T: HierarchyTag>(&self, hierarchy: &HierarchyViewMut<T>) -> HierarchyCommand {
+        match self {
+            HierarchyCommand::Attach { id, .. }
+            | HierarchyCommand::Detach { id }
+            | HierarchyCommand::Move { id, .. } => match location(hierarchy, *id) {
+                Some((parent, index)) => HierarchyCommand::Move {
+                    id: *id,
+                    parent,
+                    index,
+                },
+                None => HierarchyCommand::Detach { id: *id },
+            },
+            HierarchyCommand::Reorder { parent, .. } => HierarchyCommand::Reorder {
+                parent: *parent,
+                children: hierarchy.children(*parent).collect(),
+            },
+            HierarchyCommand::Remove { root } => HierarchyCommand::Restore {
+                root: *root,
+                location: location(hierarchy, *root),
+                // depth first, so every parent is back in place before its children
+                // and appending restores the sibling order
+                descendants: hierarchy
+                    .descendants_depth_first(*root)
+                    .map(|id| (id, hierarchy.child_storage[id].parent))
+                    .collect(),
+            },
+            HierarchyCommand::Restore { root, .. } => HierarchyCommand::Remove { root: *root },
+        }
+    }
+
+    // Applies the command and returns its inverse
+    pub fn apply<T: HierarchyTag>(&self, hierarchy: &mut HierarchyViewMut<T>) -> HierarchyCommand {
+        let inverse = self.invert(hierarchy);
+
+        match self {
+            HierarchyCommand::Attach { id, parent } => hierarchy.attach(*id, *parent),
+            HierarchyCommand::Detach { id } => hierarchy.detach(*id),
+            HierarchyCommand::Move { id, parent, index } => {
+                hierarchy.attach_at(*id, *parent, *index)
+            }
+            HierarchyCommand::Reorder { parent, children } => {
+                // anything not in the list keeps its relative order at the end
+                let position = |id: &EntityId| {
+                    children
+                        .iter()
+                        .position(|child| child == id)
+                        .unwrap_or(children.len())
+                };
+                hierarchy.sort_children_by(*parent, |a, b| position(a).cmp(&position(b)));
+            }
+            HierarchyCommand::Remove { root } => hierarchy.remove(*root),
+            HierarchyCommand::Restore {
+                root,
+                location,
+                descendants,
+            } => {
+                if let Some((parent, index)) = location {
+                    hierarchy.attach_at(*root, *parent, *index);
+                }
+                for (id, parent) in descendants {
+                    hierarchy.attach(*id, *parent);
+                }
+            }
+        }
+
+        inverse
+    }
+}
+
+// the parent of `id` and its index among its siblings
+fn location<T: HierarchyTag>(
+    hierarchy: &HierarchyViewMut<T>,
+    id: EntityId,
+) -> Option<(EntityId, usize)> {
+    let parent = hierarchy.child_storage.get(id).ok()?.parent;
+    let index = hierarchy.children(parent).position(|child| child == id)?;
+    Some((parent, index))
+}
@@ -1,6 +1,88 @@
 use shipyard::*;
 use std::marker::PhantomData;
 
+// Implemented on the marker type of a hierarchy
+// picks the shipyard tracking of its Parent and Child components, e.g.
+//
+// struct SceneGraph;
+// impl HierarchyTag for SceneGraph {
+//     type Tracking = track::All;
+// }
+pub trait HierarchyTag: Send + Sync + 'static {
+    type Tracking: HierarchyTracking;
+}
+
+// shipyard only implements IndexMut per tracking type
+// this lets the hierarchy mutate its components without knowing which one it got
+pub trait HierarchyTracking: track::Tracking {
+    fn get_mut<'a, C: Component<Tracking = Self>>(
+        storage: &'a mut ViewMut<'_, C>,
+        id: EntityId,
+    ) -> Option<&'a mut C>;
+
+    // takes a component out in the way this tracking records
+    // deleting keeps the old value around, but track::Removal only records removals
+    fn discard<C: Component<Tracking = Self>>(storage: &mut ViewMut<'_, C>, id: EntityId);
+}
+
+macro_rules! impl_hierarchy_tracking {
+    ($($tracking: ty => $discard: ident),+) => {
+        $(
+            impl HierarchyTracking for $tracking {
+                fn get_mut<'a, C: Component<Tracking = Self>>(
+                    storage: &'a mut ViewMut<'_, C>,
+                    id: EntityId,
+                ) -> Option<&'a mut C> {
+                    if storage.contains(id) {
+                        Some(&mut storage[id])
+                    } else {
+                        None
+                    }
+                }
+
+                fn discard<C: Component<Tracking = Self>>(storage: &mut ViewMut<'_, C>, id: EntityId) {
+                    storage.$discard(id);
+                }
+            }
+        )+
+    };
+}
+
+impl_hierarchy_tracking!(
+    track::Untracked => delete,
+    track::Insertion => delete,
+    track::Modification => delete,
+    track::Deletion => delete,
+    track::Removal => remove,
+    track::All => delete
+);
+
+// IndexMut-like access for any storage whose tracking is a HierarchyTracking (which is all of them)
+// writing through it flags the component as modified when the storage tracks modification
+pub(crate) trait TrackedMut<C> {
+    fn try_tracked_mut(&mut self, id: EntityId) -> Option<&mut C>;
+
+    fn tracked_mut(&mut self, id: EntityId) -> &mut C {
+        self.try_tracked_mut(id).unwrap()
+    }
+
+    // delete, or remove when only removals are tracked
+    fn discard(&mut self, id: EntityId);
+}
+
+impl<C: Component> TrackedMut<C> for ViewMut<'_, C>
+where
+    C::Tracking: HierarchyTracking,
+{
+    fn try_tracked_mut(&mut self, id: EntityId) -> Option<&mut C> {
+        C::Tracking::get_mut(self, id)
+    }
+
+    fn discard(&mut self, id: EntityId) {
+        C::Tracking::discard(self, id);
+    }
+}
+
 pub struct Parent<T> {
     pub num_children: usize,
     pub first_child: EntityId,
@@ -9,11 +91,37 @@ pub struct Parent<T> {
 
 impl<T> Component for Parent<T>
 where
-    T: 'static,
+    T: HierarchyTag,
 {
-    type Tracking = track::Untracked;
+    type Tracking = T::Tracking;
 }
 
+// Manual impls so that the marker type doesn't need to implement anything
+impl<T> std::fmt::Debug for Parent<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Parent")
+            .field("num_children", &self.num_children)
+            .field("first_child", &self.first_child)
+            .finish()
+    }
+}
+
+impl<T> Clone for Parent<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Parent<T> {}
+
+impl<T> PartialEq for Parent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_children == other.num_children && self.first_child == other.first_child
+    }
+}
+
+impl<T> Eq for Parent<T> {}
+
 impl<T> Parent<T> {
     pub fn new(num_children: usize, first_child: EntityId) -> Self {
         Self {
@@ -33,11 +141,37 @@ pub struct Child<T> {
 
 impl<T> Component for Child<T>
 where
-    T: 'static,
+    T: HierarchyTag,
 {
-    type Tracking = track::Untracked;
+    type Tracking = T::Tracking;
 }
 
+impl<T> std::fmt::Debug for Child<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Child")
+            .field("parent", &self.parent)
+            .field("prev", &self.prev)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+impl<T> Clone for Child<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Child<T> {}
+
+impl<T> PartialEq for Child<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent && self.prev == other.prev && self.next == other.next
+    }
+}
+
+impl<T> Eq for Child<T> {}
+
 impl<T> Child<T> {
     pub fn new(parent: EntityId, prev: EntityId, next: EntityId) -> Self {
         Self {
@@ -1,42 +1,66 @@
 use super::*;
 use shipyard::*;
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
 /*
-    The type signatures here are a bit intense. Some help from leudz:
+    The type signatures here are a bit intense, they're the same as HierarchyIter's
+    so that the (P, C) tuple, &HierarchyView and &HierarchyViewMut can all be printed
 
-    * P: IntoIter let us call iter() on P. This will give us <P as IntoIter>::IntoIter, whatever that is
-
-    Also, I'm not entirely sure if the debug printing is entirely correct. It's tested a bit but I'm not super confident
-    Seems to work so far though!
+    Indentation comes straight from the depth reported by descendants_depth_first_with_depth
 */
 
-pub trait HierarchyIterDebug<'a, P, C, T> {
-    fn debug_tree<F>(&'a self, root: EntityId, get_label: F) -> DebugHierarchyTree<'a, P, C, F>
-    where
-        F: Fn(EntityId) -> String;
-}
-
-impl<'a, P, C, T: 'a> HierarchyIterDebug<'a, P, C, T> for (P, C)
+pub trait HierarchyIterDebug<'a, P, C, T: 'a>: HierarchyIter<'a, P, C, T>
 where
-    P: Get<Out = &'a Parent<T>> + Copy + IntoIter,
+    P: Get<Out = &'a Parent<T>> + Copy,
     C: Get<Out = &'a Child<T>> + Copy,
 {
-    fn debug_tree<F>(&'a self, root: EntityId, get_label: F) -> DebugHierarchyTree<'a, P, C, F>
+    fn debug_tree<F>(self, root: EntityId, get_label: F) -> DebugHierarchyTree<P, C, F>
+    where
+        F: Fn(EntityId) -> String,
+    {
+        DebugHierarchyTree(self.storages(), root, get_label)
+    }
+
+    fn dot_tree<F>(self, root: EntityId, get_label: F) -> DotHierarchyTree<P, C, F>
+    where
+        F: Fn(EntityId) -> String,
+    {
+        DotHierarchyTree {
+            storages: self.storages(),
+            root,
+            get_label,
+            sibling_edges: false,
+        }
+    }
+
+    fn display_tree<F>(self, root: EntityId, get_label: F) -> DisplayHierarchyTree<P, C, F>
     where
         F: Fn(EntityId) -> String,
     {
-        DebugHierarchyTree(self, root, get_label)
+        DisplayHierarchyTree {
+            storages: self.storages(),
+            root,
+            get_label,
+            ascii: false,
+            max_depth: None,
+            show_ids: false,
+        }
     }
 }
 
-pub struct DebugHierarchyTree<'a, P, C, F>(&'a (P, C), EntityId, F)
+impl<'a, H, P, C, T: 'a> HierarchyIterDebug<'a, P, C, T> for H
+where
+    H: HierarchyIter<'a, P, C, T>,
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+}
+
+pub struct DebugHierarchyTree<P, C, F>((P, C), EntityId, F)
 where
     F: Fn(EntityId) -> String;
 
-impl<'a, P, C, F, T: 'a> std::fmt::Debug for DebugHierarchyTree<'a, P, C, F>
+impl<'a, P, C, F, T: 'a> std::fmt::Debug for DebugHierarchyTree<P, C, F>
 where
-    P: Get<Out = &'a Parent<T>> + Copy + IntoIter,
+    P: Get<Out = &'a Parent<T>> + Copy,
     C: Get<Out = &'a Child<T>> + Copy,
     F: Fn(EntityId) -> String,
 {
@@ -46,44 +70,218 @@ where
         let root = self.1;
         let get_label = &(self.2);
 
-        let mut depth_map = HashMap::<EntityId, usize>::new();
-        let mut depth = 1;
-        let mut last_parent = root;
+        writeln!(f, "{}", get_label(root))?;
+        for (entity_id, depth) in
+            (parent_storage, child_storage).descendants_depth_first_with_depth(root)
+        {
+            writeln!(f, "{}{}", "  ".repeat(depth), get_label(entity_id))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Graphviz output - render with e.g. `dot -Tsvg tree.dot -o tree.svg`
+pub struct DotHierarchyTree<P, C, F>
+where
+    F: Fn(EntityId) -> String,
+{
+    storages: (P, C),
+    root: EntityId,
+    get_label: F,
+    sibling_edges: bool,
+}
+
+impl<P, C, F> DotHierarchyTree<P, C, F>
+where
+    F: Fn(EntityId) -> String,
+{
+    // Also draw a dashed edge from each child to its next sibling
+    pub fn with_sibling_edges(mut self, sibling_edges: bool) -> Self {
+        self.sibling_edges = sibling_edges;
+        self
+    }
+}
+
+impl<'a, P, C, F, T: 'a> std::fmt::Display for DotHierarchyTree<P, C, F>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+    F: Fn(EntityId) -> String,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (parent_storage, child_storage) = self.storages;
+
+        fn node_id(entity_id: EntityId) -> String {
+            format!("e{}_{}", entity_id.index(), entity_id.gen())
+        }
+
+        fn escape(label: &str) -> String {
+            label.replace('\\', "\\\\").replace('"', "\\\"")
+        }
 
-        depth_map.insert(root, depth);
+        writeln!(f, "digraph hierarchy {{")?;
+        writeln!(
+            f,
+            "    {} [label=\"{}\"];",
+            node_id(self.root),
+            escape(&(self.get_label)(self.root))
+        )?;
 
-        fn get_spaces(entity_id: EntityId, depth_map: &HashMap<EntityId, usize>) -> String {
-            let n = depth_map.get(&entity_id).unwrap();
-            let mut s = "".to_string();
-            for _ in 0..*n {
-                s += "  ";
+        for entity_id in (parent_storage, child_storage).descendants_depth_first(self.root) {
+            let child = child_storage.get(entity_id).unwrap();
+
+            writeln!(
+                f,
+                "    {} [label=\"{}\"];",
+                node_id(entity_id),
+                escape(&(self.get_label)(entity_id))
+            )?;
+            writeln!(
+                f,
+                "    {} -> {};",
+                node_id(child.parent),
+                node_id(entity_id)
+            )?;
+
+            // the sibling list is a ring, don't draw the edge that wraps around
+            if self.sibling_edges
+                && child.next != parent_storage.get(child.parent).unwrap().first_child
+            {
+                writeln!(
+                    f,
+                    "    {} -> {} [style=dashed, constraint=false];",
+                    node_id(entity_id),
+                    node_id(child.next)
+                )?;
             }
-            s
         }
 
-        writeln!(f, "{}", get_label(root))?;
-        for entity_id in (parent_storage, child_storage).descendants_depth_first(root) {
-            let parent = child_storage.get(entity_id).unwrap().parent;
-            match depth_map.entry(parent) {
-                Entry::Vacant(entry) => {
-                    depth += 1;
-                    entry.insert(depth);
-                }
-                Entry::Occupied(_) => {
-                    if parent != last_parent {
-                        depth -= 1;
-                    }
-                }
+        writeln!(f, "}}")
+    }
+}
+
+// Renders like the `tree` command:
+//
+// root
+// ├── a
+// │   └── b
+// └── c
+pub struct DisplayHierarchyTree<P, C, F>
+where
+    F: Fn(EntityId) -> String,
+{
+    storages: (P, C),
+    root: EntityId,
+    get_label: F,
+    ascii: bool,
+    max_depth: Option<usize>,
+    show_ids: bool,
+}
+
+impl<P, C, F> DisplayHierarchyTree<P, C, F>
+where
+    F: Fn(EntityId) -> String,
+{
+    // Use `|--`, `` `-- `` and `|` instead of box-drawing characters
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    // Don't print anything deeper than `max_depth` (the root is at depth 0)
+    // Elided subtrees are summarized as "… N more"
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // Print the EntityId as `(index.gen)` after each label
+    pub fn show_ids(mut self, show_ids: bool) -> Self {
+        self.show_ids = show_ids;
+        self
+    }
+
+    fn label(&self, entity_id: EntityId) -> String {
+        if self.show_ids {
+            format!(
+                "{} ({}.{})",
+                (self.get_label)(entity_id),
+                entity_id.index(),
+                entity_id.gen()
+            )
+        } else {
+            (self.get_label)(entity_id)
+        }
+    }
+}
+
+impl<'a, P, C, F, T: 'a> std::fmt::Display for DisplayHierarchyTree<P, C, F>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+    F: Fn(EntityId) -> String,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (parent_storage, child_storage) = self.storages;
+
+        let (branch, last_branch, pipe, blank, ellipsis) = if self.ascii {
+            ("|-- ", "`-- ", "|   ", "    ", "...")
+        } else {
+            ("├── ", "└── ", "│   ", "    ", "…")
+        };
+
+        // whether the ancestor at each depth was the last of its siblings
+        // that decides if a pipe needs to continue down past it
+        let mut last_at_depth: Vec<bool> = Vec::new();
+
+        let write_prefix = |f: &mut std::fmt::Formatter, last_at_depth: &[bool]| {
+            for is_last in last_at_depth {
+                f.write_str(if *is_last { blank } else { pipe })?;
+            }
+            Ok(())
+        };
+
+        writeln!(f, "{}", self.label(self.root))?;
+
+        let elide = |f: &mut std::fmt::Formatter, entity_id, last_at_depth: &[bool]| {
+            let hidden = (parent_storage, child_storage)
+                .descendants_depth_first(entity_id)
+                .count();
+            if hidden > 0 {
+                write_prefix(f, last_at_depth)?;
+                writeln!(f, "{}{} {} more", last_branch, ellipsis, hidden)?;
             }
+            Ok(())
+        };
 
-            last_parent = parent;
+        if self.max_depth == Some(0) {
+            return elide(f, self.root, &last_at_depth);
+        }
 
+        for (entity_id, depth) in
+            (parent_storage, child_storage).descendants_depth_first_with_depth(self.root)
+        {
+            if self.max_depth.is_some_and(|max_depth| depth > max_depth) {
+                continue;
+            }
+
+            let child = child_storage.get(entity_id).unwrap();
+            let is_last = child.next == parent_storage.get(child.parent).unwrap().first_child;
+
+            last_at_depth.truncate(depth - 1);
+            write_prefix(f, &last_at_depth)?;
             writeln!(
                 f,
                 "{}{}",
-                get_spaces(parent, &depth_map),
-                get_label(entity_id)
+                if is_last { last_branch } else { branch },
+                self.label(entity_id)
             )?;
+            last_at_depth.push(is_last);
+
+            if self.max_depth == Some(depth) {
+                elide(f, entity_id, &last_at_depth)?;
+            }
         }
 
         Ok(())
@@ -0,0 +1,305 @@
+use super::*;
+use shipyard::info::TypeInfo;
+use shipyard::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+
+// Deferred hierarchy edits
+//
+// Systems borrow HierarchyCommandsView<T>, which only needs shared access, so they can run in parallel.
+// Each borrow queues its commands locally and hands them over as one batch when dropped.
+// flush_hierarchy_commands::<T> then applies all the batches.
+//
+// Batches are applied in an order derived from their content rather than from when they were handed over,
+// so the result doesn't depend on how the systems happened to be scheduled.
+// Comparators can't be compared though, batches only differing by them need distinct set_order()s.
+// Commands within a batch always keep their order.
+//
+// The entities created for attach_new can be looked up with resolved() after the flush.
+pub struct HierarchyCommands<T> {
+    batches: Mutex<Vec<Batch>>,
+    next_batch: AtomicUsize,
+    resolved: HashMap<ReservedEntity, EntityId>,
+    marker: PhantomData<T>,
+}
+
+struct Batch {
+    id: usize,
+    order: u32,
+    commands: Vec<QueuedCommand>,
+}
+
+impl<T: HierarchyTag> Unique for HierarchyCommands<T> {
+    type Tracking = track::Untracked;
+}
+
+impl<T> Default for HierarchyCommands<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HierarchyCommands<T> {
+    pub fn new() -> Self {
+        Self {
+            batches: Mutex::new(Vec::new()),
+            next_batch: AtomicUsize::new(0),
+            resolved: HashMap::new(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batches.lock().unwrap().is_empty()
+    }
+
+    // The entity created for a reservation by the last flush
+    // None if it was queued after that flush
+    pub fn resolved(&self, reserved: ReservedEntity) -> Option<EntityId> {
+        self.resolved.get(&reserved).copied()
+    }
+
+    fn take(&self) -> Vec<Batch> {
+        std::mem::take(&mut *self.batches.lock().unwrap())
+    }
+}
+
+// An entity queued by attach_new, it only exists once the commands are flushed
+// It can only be used with the HierarchyCommandsView that returned it, other views panic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReservedEntity {
+    batch: usize,
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommandTarget {
+    Entity(EntityId),
+    Reserved(ReservedEntity),
+}
+
+impl From<EntityId> for CommandTarget {
+    fn from(id: EntityId) -> Self {
+        CommandTarget::Entity(id)
+    }
+}
+
+impl From<ReservedEntity> for CommandTarget {
+    fn from(reserved: ReservedEntity) -> Self {
+        CommandTarget::Reserved(reserved)
+    }
+}
+
+type Compare = Box<dyn FnMut(&EntityId, &EntityId) -> Ordering + Send>;
+
+enum QueuedCommand {
+    Attach {
+        id: CommandTarget,
+        parent: CommandTarget,
+    },
+    AttachNew {
+        parent: CommandTarget,
+    },
+    Remove {
+        id: CommandTarget,
+    },
+    SortChildrenBy {
+        parent: CommandTarget,
+        compare: Compare,
+    },
+}
+
+// reservations are compared by index only
+// their batch comes from when the view was borrowed, which depends on scheduling
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum TargetKey {
+    Entity(EntityId),
+    Reserved(usize),
+}
+
+impl From<CommandTarget> for TargetKey {
+    fn from(target: CommandTarget) -> Self {
+        match target {
+            CommandTarget::Entity(id) => TargetKey::Entity(id),
+            CommandTarget::Reserved(reserved) => TargetKey::Reserved(reserved.index),
+        }
+    }
+}
+
+impl QueuedCommand {
+    // everything but the comparators, used to order the batches
+    fn key(&self) -> (u8, TargetKey, Option<TargetKey>) {
+        match self {
+            QueuedCommand::Attach { id, parent } => (0, (*id).into(), Some((*parent).into())),
+            QueuedCommand::AttachNew { parent } => (1, (*parent).into(), None),
+            QueuedCommand::Remove { id } => (2, (*id).into(), None),
+            QueuedCommand::SortChildrenBy { parent, .. } => (3, (*parent).into(), None),
+        }
+    }
+}
+
+pub struct HierarchyCommandsView<'v, T: HierarchyTag> {
+    commands: UniqueView<'v, HierarchyCommands<T>>,
+    queue: Vec<QueuedCommand>,
+    batch: usize,
+    order: u32,
+    reserved: usize,
+}
+
+impl<T: HierarchyTag> HierarchyCommandsView<'_, T> {
+    pub fn attach(&mut self, id: impl Into<CommandTarget>, parent: impl Into<CommandTarget>) {
+        let command = QueuedCommand::Attach {
+            id: self.target(id),
+            parent: self.target(parent),
+        };
+        self.queue.push(command);
+    }
+
+    pub fn attach_new(&mut self, parent: impl Into<CommandTarget>) -> ReservedEntity {
+        let reserved = ReservedEntity {
+            batch: self.batch,
+            index: self.reserved,
+        };
+        self.reserved += 1;
+        let command = QueuedCommand::AttachNew {
+            parent: self.target(parent),
+        };
+        self.queue.push(command);
+        reserved
+    }
+
+    pub fn remove(&mut self, id: impl Into<CommandTarget>) {
+        let command = QueuedCommand::Remove {
+            id: self.target(id),
+        };
+        self.queue.push(command);
+    }
+
+    // Batches are applied by increasing order first, then by content (0 by default)
+    pub fn set_order(&mut self, order: u32) {
+        self.order = order;
+    }
+
+    fn target(&self, target: impl Into<CommandTarget>) -> CommandTarget {
+        let target = target.into();
+        if let CommandTarget::Reserved(reserved) = target {
+            assert_eq!(
+                reserved.batch, self.batch,
+                "a ReservedEntity can only be used with the HierarchyCommandsView that returned it"
+            );
+        }
+        target
+    }
+
+    // the comparator runs at flush time, so it has to own whatever it looks at
+    pub fn sort_children_by<F>(&mut self, parent: impl Into<CommandTarget>, compare: F)
+    where
+        F: FnMut(&EntityId, &EntityId) -> Ordering + Send + 'static,
+    {
+        let command = QueuedCommand::SortChildrenBy {
+            parent: self.target(parent),
+            compare: Box::new(compare),
+        };
+        self.queue.push(command);
+    }
+}
+
+impl<T: HierarchyTag> Drop for HierarchyCommandsView<'_, T> {
+    fn drop(&mut self) {
+        if !self.queue.is_empty() {
+            let batch = Batch {
+                id: self.batch,
+                order: self.order,
+                commands: std::mem::take(&mut self.queue),
+            };
+            self.commands.batches.lock().unwrap().push(batch);
+        }
+    }
+}
+
+pub struct HierarchyCommandsViewBorrower<T>(PhantomData<T>);
+
+impl<T: HierarchyTag> IntoBorrow for HierarchyCommandsView<'_, T> {
+    type Borrow = HierarchyCommandsViewBorrower<T>;
+}
+
+impl<'v, T: HierarchyTag> Borrow<'v> for HierarchyCommandsViewBorrower<T> {
+    type View = HierarchyCommandsView<'v, T>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        let commands = <UniqueView<'v, HierarchyCommands<T>> as IntoBorrow>::Borrow::borrow(
+            world, last_run, current,
+        )?;
+        // every view gets its own batch id, so reservations can't be mixed up between them
+        let batch = commands.next_batch.fetch_add(1, AtomicOrdering::Relaxed);
+        Ok(HierarchyCommandsView {
+            commands,
+            queue: Vec::new(),
+            batch,
+            order: 0,
+            reserved: 0,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag> BorrowInfo for HierarchyCommandsView<'_, T> {
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <UniqueView<'_, HierarchyCommands<T>>>::borrow_info(info);
+    }
+}
+
+// The system applying everything queued through HierarchyCommandsView<T>
+// the ids created for attach_new are kept in HierarchyCommands<T> until the next flush
+pub fn flush_hierarchy_commands<T: HierarchyTag>(
+    mut commands: UniqueViewMut<HierarchyCommands<T>>,
+    mut hierarchy: HierarchyViewMut<T>,
+) {
+    let mut batches = commands.take();
+    batches.sort_by(|a, b| {
+        a.order.cmp(&b.order).then_with(|| {
+            a.commands
+                .iter()
+                .map(QueuedCommand::key)
+                .cmp(b.commands.iter().map(QueuedCommand::key))
+        })
+    });
+
+    commands.resolved.clear();
+    for batch in batches {
+        let mut reserved = Vec::new();
+
+        for command in batch.commands {
+            // the views only accept their own reservations, which come in order
+            let resolve = |target, reserved: &[EntityId]| match target {
+                CommandTarget::Entity(id) => id,
+                CommandTarget::Reserved(ReservedEntity { index, .. }) => reserved[index],
+            };
+
+            match command {
+                QueuedCommand::Attach { id, parent } => {
+                    hierarchy.attach(resolve(id, &reserved), resolve(parent, &reserved))
+                }
+                QueuedCommand::AttachNew { parent } => {
+                    let id = hierarchy.attach_new(resolve(parent, &reserved));
+                    let reservation = ReservedEntity {
+                        batch: batch.id,
+                        index: reserved.len(),
+                    };
+                    commands.resolved.insert(reservation, id);
+                    reserved.push(id);
+                }
+                QueuedCommand::Remove { id } => hierarchy.remove(resolve(id, &reserved)),
+                QueuedCommand::SortChildrenBy { parent, compare } => {
+                    hierarchy.sort_children_by(resolve(parent, &reserved), compare)
+                }
+            }
+        }
+    }
+}
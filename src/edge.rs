@@ -0,0 +1,305 @@
+use super::*;
+use shipyard::info::TypeInfo;
+use shipyard::*;
+use std::marker::PhantomData;
+
+// Data on the link between a child and its parent (joint constraints, slot names, layout weights...)
+// it's a side storage keyed by the child, so it follows the child around its sibling list
+// and goes away together with the link when edited through HierarchyEdgesViewMut
+pub struct Edge<T, E> {
+    pub data: E,
+    marker: PhantomData<T>,
+}
+
+impl<T, E> Component for Edge<T, E>
+where
+    T: HierarchyTag,
+    E: Send + Sync + 'static,
+{
+    type Tracking = T::Tracking;
+}
+
+impl<T, E> Edge<T, E> {
+    pub fn new(data: E) -> Self {
+        Self {
+            data,
+            marker: PhantomData,
+        }
+    }
+}
+
+// Manual impls so that the marker type doesn't need to implement anything
+impl<T, E: std::fmt::Debug> std::fmt::Debug for Edge<T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Edge").field("data", &self.data).finish()
+    }
+}
+
+impl<T, E: Clone> Clone for Edge<T, E> {
+    fn clone(&self) -> Self {
+        Self::new(self.data.clone())
+    }
+}
+
+impl<T, E: PartialEq> PartialEq for Edge<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T, E: Eq> Eq for Edge<T, E> {}
+
+// A HierarchyViewMut that also keeps the Edge<T, E> storage in sync
+// attaching somewhere else, detaching or removing drops the edge data of the links that went away
+// reordering siblings keeps it
+pub struct HierarchyEdgesViewMut<'v, T: HierarchyTag, E: Send + Sync + 'static> {
+    pub hierarchy: HierarchyViewMut<'v, T>,
+    pub edge_storage: ViewMut<'v, Edge<T, E>>,
+}
+
+pub struct HierarchyEdgesViewMutBorrower<T, E>(PhantomData<(T, E)>);
+
+impl<T: HierarchyTag, E: Send + Sync + 'static> IntoBorrow for HierarchyEdgesViewMut<'_, T, E> {
+    type Borrow = HierarchyEdgesViewMutBorrower<T, E>;
+}
+
+impl<'v, T: HierarchyTag, E: Send + Sync + 'static> Borrow<'v>
+    for HierarchyEdgesViewMutBorrower<T, E>
+{
+    type View = HierarchyEdgesViewMut<'v, T, E>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(HierarchyEdgesViewMut {
+            hierarchy: <HierarchyViewMut<'v, T> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            edge_storage: <ViewMut<'v, Edge<T, E>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag, E: Send + Sync + 'static> BorrowInfo
+    for HierarchyEdgesViewMut<'_, T, E>
+{
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <HierarchyViewMut<'_, T>>::borrow_info(info);
+        <ViewMut<'_, Edge<T, E>>>::borrow_info(info);
+    }
+}
+
+impl<T: HierarchyTag, E: Send + Sync + 'static> HierarchyEdgesViewMut<'_, T, E> {
+    // the data on the link between `id` and its parent
+    pub fn edge(&self, id: EntityId) -> Option<&E> {
+        self.edge_storage.get(id).ok().map(|edge| &edge.data)
+    }
+
+    pub fn edge_mut(&mut self, id: EntityId) -> Option<&mut E> {
+        self.edge_storage
+            .try_tracked_mut(id)
+            .map(|edge| &mut edge.data)
+    }
+
+    // Attaches an entity as the last child of `parent`, with `data` on the new link
+    pub fn attach_with(&mut self, id: EntityId, parent: EntityId, data: E) {
+        self.attach_at_with(id, parent, usize::MAX, data);
+    }
+
+    // Attaches an entity as the `index`th child of `parent`, with `data` on the new link
+    pub fn attach_at_with(&mut self, id: EntityId, parent: EntityId, index: usize, data: E) {
+        self.hierarchy.attach_at(id, parent, index);
+        let HierarchyEdgesViewMut {
+            hierarchy,
+            edge_storage,
+        } = self;
+        hierarchy
+            .entities
+            .add_component(id, &mut *edge_storage, Edge::new(data));
+    }
+
+    // Creates a new entity and attaches it to `parent`, with `data` on the new link
+    pub fn attach_new_with(&mut self, parent: EntityId, data: E) -> EntityId {
+        let id = self.hierarchy.entities.add_entity((), ());
+        self.attach_with(id, parent, data);
+        id
+    }
+
+    fn drop_edge(&mut self, id: EntityId) {
+        self.edge_storage.discard(id);
+    }
+}
+
+impl<T: HierarchyTag, E: Send + Sync + 'static> HierarchyMut<T>
+    for HierarchyEdgesViewMut<'_, T, E>
+{
+    // the edge data is kept when `id` stays under the same parent
+    fn attach(&mut self, id: EntityId, parent: EntityId) {
+        self.attach_at(id, parent, usize::MAX);
+    }
+
+    fn attach_at(&mut self, id: EntityId, parent: EntityId, index: usize) {
+        if self.hierarchy.ancestors(id).next() != Some(parent) {
+            self.drop_edge(id);
+        }
+        self.hierarchy.attach_at(id, parent, index);
+    }
+
+    fn attach_new(&mut self, parent: EntityId) -> EntityId {
+        self.hierarchy.attach_new(parent)
+    }
+
+    fn attach_many(&mut self, parent: EntityId, ids: &[EntityId]) {
+        let ids = attachable(&self.hierarchy, parent, ids);
+        for &id in &ids {
+            if self.hierarchy.ancestors(id).next() != Some(parent) {
+                self.drop_edge(id);
+            }
+        }
+        self.hierarchy.attach_many(parent, &ids);
+    }
+
+    fn attach_new_many(&mut self, parent: EntityId, count: usize) -> Vec<EntityId> {
+        self.hierarchy.attach_new_many(parent, count)
+    }
+
+    fn detach(&mut self, id: EntityId) {
+        self.drop_edge(id);
+        self.hierarchy.detach(id);
+    }
+
+    fn remove_single_with(&mut self, id: EntityId, policy: ChildPolicy) -> bool {
+        // the edges stay if the hierarchy refuses
+        if let ChildPolicy::ReparentTo(parent) = policy {
+            if self
+                .hierarchy
+                .ancestors_inclusive(parent)
+                .any(|ancestor| ancestor == id)
+            {
+                return false;
+            }
+        }
+        for child_id in self.hierarchy.children(id).collect::<Vec<_>>() {
+            self.drop_edge(child_id);
+        }
+        self.drop_edge(id);
+        self.hierarchy.remove_single_with(id, policy)
+    }
+
+    fn remove_single(&mut self, id: EntityId) {
+        for child_id in self.hierarchy.children(id).collect::<Vec<_>>() {
+            self.drop_edge(child_id);
+        }
+        self.drop_edge(id);
+        self.hierarchy.remove_single(id);
+    }
+
+    fn remove(&mut self, id: EntityId) {
+        for descendant_id in self
+            .hierarchy
+            .descendants_depth_first(id)
+            .collect::<Vec<_>>()
+        {
+            self.drop_edge(descendant_id);
+        }
+        self.drop_edge(id);
+        self.hierarchy.remove(id);
+    }
+
+    fn sort_children_by<F>(&mut self, id: EntityId, compare: F)
+    where
+        F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering,
+    {
+        self.hierarchy.sort_children_by(id, compare);
+    }
+
+    fn sort_children_by_key<K, F>(&mut self, id: EntityId, key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K,
+    {
+        self.hierarchy.sort_children_by_key(id, key);
+    }
+
+    fn sort_children_by_cached_key<K, F>(&mut self, id: EntityId, key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K,
+    {
+        self.hierarchy.sort_children_by_cached_key(id, key);
+    }
+
+    fn sort_descendants_by<F>(&mut self, id: EntityId, compare: F)
+    where
+        F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering,
+    {
+        self.hierarchy.sort_descendants_by(id, compare);
+    }
+
+    fn move_child_to(&mut self, id: EntityId, index: usize) {
+        self.hierarchy.move_child_to(id, index);
+    }
+
+    fn swap_siblings(&mut self, a: EntityId, b: EntityId) {
+        self.hierarchy.swap_siblings(a, b);
+    }
+
+    fn reverse_children(&mut self, parent: EntityId) {
+        self.hierarchy.reverse_children(parent);
+    }
+
+    fn bring_to_front(&mut self, id: EntityId) {
+        self.hierarchy.bring_to_front(id);
+    }
+
+    fn send_to_back(&mut self, id: EntityId) {
+        self.hierarchy.send_to_back(id);
+    }
+
+    fn ungroup(&mut self, id: EntityId) {
+        for child_id in self.hierarchy.children(id).collect::<Vec<_>>() {
+            self.drop_edge(child_id);
+        }
+        self.drop_edge(id);
+        self.hierarchy.ungroup(id);
+    }
+
+    fn group(&mut self, ids: &[EntityId], new_parent: EntityId) -> bool {
+        let old_parent = self.hierarchy.ancestors(new_parent).next();
+        if !self.hierarchy.group(ids, new_parent) {
+            return false;
+        }
+        // new_parent keeps its link if it was already a sibling of the run
+        if old_parent != self.hierarchy.ancestors(new_parent).next() {
+            self.drop_edge(new_parent);
+        }
+        for &id in ids {
+            self.drop_edge(id);
+        }
+        true
+    }
+
+    fn set_children(&mut self, parent: EntityId, children: &[EntityId]) {
+        // links that stay keep their data
+        let children = attachable(&self.hierarchy, parent, children);
+        let moved = self
+            .hierarchy
+            .children(parent)
+            .filter(|id| !children.contains(id))
+            .chain(
+                children
+                    .iter()
+                    .copied()
+                    .filter(|id| self.hierarchy.ancestors(*id).next() != Some(parent)),
+            )
+            .collect::<Vec<_>>();
+        for id in moved {
+            self.drop_edge(id);
+        }
+        self.hierarchy.set_children(parent, &children);
+    }
+}
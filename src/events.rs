@@ -0,0 +1,79 @@
+use super::*;
+use shipyard::*;
+use std::marker::PhantomData;
+
+// Structural edits made through HierarchyMut
+// only recorded if a HierarchyEvents<T> unique was added to the World
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyEvent {
+    // `id` was attached as the `index`th child of `parent`
+    Attached {
+        id: EntityId,
+        parent: EntityId,
+        index: usize,
+    },
+    // `id` was detached from `old_parent` and is now a root
+    Detached {
+        id: EntityId,
+        old_parent: EntityId,
+    },
+    // the order of `parent`'s children changed
+    Reordered {
+        parent: EntityId,
+    },
+    // `root` was taken out of the hierarchy
+    // after remove(), that's the whole subtree, after remove_single() its children were detached first
+    Removed {
+        root: EntityId,
+    },
+}
+
+// The event log of a hierarchy, e.g. `world.add_unique(HierarchyEvents::<MyTree>::new())`
+// drain it from a system every frame with UniqueViewMut<HierarchyEvents<MyTree>>
+pub struct HierarchyEvents<T> {
+    events: Vec<HierarchyEvent>,
+    marker: PhantomData<T>,
+}
+
+impl<T: HierarchyTag> Unique for HierarchyEvents<T> {
+    type Tracking = track::Untracked;
+}
+
+impl<T> Default for HierarchyEvents<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HierarchyEvents<T> {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, HierarchyEvent> {
+        self.events.iter()
+    }
+
+    pub fn drain(&mut self) -> std::vec::Drain<'_, HierarchyEvent> {
+        self.events.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub(crate) fn push(&mut self, event: HierarchyEvent) {
+        self.events.push(event);
+    }
+}
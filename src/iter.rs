@@ -2,6 +2,7 @@ use super::*;
 use shipyard::*;
 use std::collections::VecDeque;
 
+#[derive(Debug, Clone)]
 pub struct ChildrenIter<C> {
     pub child_storage: C,
     pub cursor: (EntityId, usize),
@@ -31,6 +32,7 @@ where
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct AncestorIter<C> {
     pub child_storage: C,
     pub cursor: EntityId,
@@ -50,6 +52,32 @@ where
     }
 }
 
+// The ancestors up to and including the first one matching the predicate
+#[derive(Debug, Clone)]
+pub struct AncestorsUntilIter<C, F> {
+    pub ancestors: AncestorIter<C>,
+    pub predicate: F,
+    pub done: bool,
+}
+
+impl<'a, C, T: 'a, F> Iterator for AncestorsUntilIter<C, F>
+where
+    C: Get<Out = &'a Child<T>> + Copy,
+    F: FnMut(EntityId) -> bool,
+{
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let id = self.ancestors.next()?;
+        self.done = (self.predicate)(id);
+        Some(id)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct DescendantsDepthFirstIter<P, C> {
     pub parent_storage: P,
     pub child_storage: C,
@@ -64,7 +92,20 @@ where
     type Item = EntityId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(cursor) = self.cursors.last_mut() {
+        self.next_with_depth().map(|(entity, _)| entity)
+    }
+}
+
+impl<'a, P, C, T: 'a> DescendantsDepthFirstIter<P, C>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    // the depth is the number of cursors on the stack when the entity is reached
+    // direct children of the starting entity are at depth 1
+    fn next_with_depth(&mut self) -> Option<(EntityId, usize)> {
+        let mut depth = self.cursors.len();
+        while let Some(cursor) = self.cursors.last_mut() {
             let (entity, num_children) = cursor;
             if *num_children > 0 {
                 *num_children -= 1;
@@ -79,17 +120,32 @@ where
                 if let Ok(parent) = self.parent_storage.get(ret) {
                     self.cursors.push((parent.first_child, parent.num_children));
                 }
-                Some(ret)
+                return Some((ret, depth));
             } else {
                 self.cursors.pop();
-                self.next()
+                depth -= 1;
             }
-        } else {
-            None
         }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescendantsDepthFirstWithDepthIter<P, C>(DescendantsDepthFirstIter<P, C>);
+
+impl<'a, P, C, T: 'a> Iterator for DescendantsDepthFirstWithDepthIter<P, C>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    type Item = (EntityId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with_depth()
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct DescendantsBreadthFirstIter<P, C> {
     pub parent_storage: P,
     pub child_storage: C,
@@ -132,28 +188,113 @@ where
     }
 }
 
-pub trait HierarchyIter<'a, P, C> {
-    fn ancestors(&self, id: EntityId) -> AncestorIter<C>;
-    fn children(&self, id: EntityId) -> ChildrenIter<C>;
-    fn descendants_depth_first(&self, id: EntityId) -> DescendantsDepthFirstIter<P, C>;
-    fn descendants_breadth_first(&self, id: EntityId) -> DescendantsBreadthFirstIter<P, C>;
+// Children before their parent, siblings in order
+// the starting entity itself is not included
+#[derive(Debug, Clone)]
+pub struct DescendantsPostOrderIter<P, C> {
+    pub parent_storage: P,
+    pub child_storage: C,
+    // (parent, next child to visit, children left)
+    pub cursors: Vec<(EntityId, EntityId, usize)>,
+}
+
+impl<'a, P, C, T: 'a> Iterator for DescendantsPostOrderIter<P, C>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((parent, cursor, num_children)) = self.cursors.last_mut() {
+            if *num_children > 0 {
+                *num_children -= 1;
+
+                let ret = *cursor;
+
+                if let Ok(child) = self.child_storage.get(ret) {
+                    *cursor = child.next;
+                } else {
+                    return None;
+                }
+                // a parent is only returned once all of its children have been
+                if let Ok(parent) = self.parent_storage.get(ret) {
+                    self.cursors
+                        .push((ret, parent.first_child, parent.num_children));
+                } else {
+                    return Some(ret);
+                }
+            } else {
+                let parent = *parent;
+                self.cursors.pop();
+                // the bottom cursor belongs to the starting entity
+                if !self.cursors.is_empty() {
+                    return Some(parent);
+                }
+            }
+        }
+        None
+    }
+}
+
+// The descendants that have a component in a storage, closest first
+#[derive(Debug, Clone)]
+pub struct DescendantsWithComponentIter<P, C, J>(
+    pub WithIter<DescendantsBreadthFirstIter<P, C>, J>,
+);
+
+impl<'a, P, C, T: 'a, J> Iterator for DescendantsWithComponentIter<P, C, J>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+    J: JoinStorage,
+{
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(id, _)| id)
+    }
 }
 
-impl<'a, P, C, T: 'a> HierarchyIter<'a, P, C> for (P, C)
+// Implemented for a (parent storage, child storage) tuple of views
+// as well as for references to HierarchyView and HierarchyViewMut
+pub trait HierarchyIter<'a, P, C, T: 'a>: Sized
 where
     P: Get<Out = &'a Parent<T>> + Copy,
     C: Get<Out = &'a Child<T>> + Copy,
 {
-    fn ancestors(&self, id: EntityId) -> AncestorIter<C> {
-        let (_, child_storage) = *self;
+    fn storages(self) -> (P, C);
+
+    fn ancestors(self, id: EntityId) -> AncestorIter<C> {
+        let (_, child_storage) = self.storages();
         AncestorIter {
             child_storage,
             cursor: id,
         }
     }
 
-    fn children(&self, id: EntityId) -> ChildrenIter<C> {
-        let (parent_storage, child_storage) = *self;
+    // `id` itself, then its ancestors
+    fn ancestors_inclusive(
+        self,
+        id: EntityId,
+    ) -> std::iter::Chain<std::iter::Once<EntityId>, AncestorIter<C>> {
+        std::iter::once(id).chain(self.ancestors(id))
+    }
+
+    // stops after the first ancestor matching `predicate`, e.g. a canvas or a prefab root
+    fn ancestors_until<F>(self, id: EntityId, predicate: F) -> AncestorsUntilIter<C, F>
+    where
+        F: FnMut(EntityId) -> bool,
+    {
+        AncestorsUntilIter {
+            ancestors: self.ancestors(id),
+            predicate,
+            done: false,
+        }
+    }
+
+    fn children(self, id: EntityId) -> ChildrenIter<C> {
+        let (parent_storage, child_storage) = self.storages();
         ChildrenIter {
             child_storage,
             cursor: parent_storage
@@ -162,8 +303,8 @@ where
         }
     }
 
-    fn descendants_depth_first(&self, id: EntityId) -> DescendantsDepthFirstIter<P, C> {
-        let (parent_storage, child_storage) = *self;
+    fn descendants_depth_first(self, id: EntityId) -> DescendantsDepthFirstIter<P, C> {
+        let (parent_storage, child_storage) = self.storages();
         DescendantsDepthFirstIter {
             parent_storage,
             child_storage,
@@ -173,8 +314,28 @@ where
             ),
         }
     }
-    fn descendants_breadth_first(&self, id: EntityId) -> DescendantsBreadthFirstIter<P, C> {
-        let (parent_storage, child_storage) = *self;
+
+    fn descendants_depth_first_with_depth(
+        self,
+        id: EntityId,
+    ) -> DescendantsDepthFirstWithDepthIter<P, C> {
+        DescendantsDepthFirstWithDepthIter(self.descendants_depth_first(id))
+    }
+
+    fn descendants_post_order(self, id: EntityId) -> DescendantsPostOrderIter<P, C> {
+        let (parent_storage, child_storage) = self.storages();
+        DescendantsPostOrderIter {
+            parent_storage,
+            child_storage,
+            cursors: parent_storage.get(id).map_or_else(
+                |_| Vec::new(),
+                |parent| vec![(id, parent.first_child, parent.num_children)],
+            ),
+        }
+    }
+
+    fn descendants_breadth_first(self, id: EntityId) -> DescendantsBreadthFirstIter<P, C> {
+        let (parent_storage, child_storage) = self.storages();
         DescendantsBreadthFirstIter {
             parent_storage,
             child_storage,
@@ -188,4 +349,93 @@ where
             ),
         }
     }
+
+    // the children of `id` that have a component in `storage`, along with it
+    // pass `&mut view_mut` to get mutable references
+    fn children_with<S: IntoJoin>(
+        self,
+        id: EntityId,
+        storage: S,
+    ) -> WithIter<ChildrenIter<C>, S::Join> {
+        WithIter {
+            iter: self.children(id),
+            storage: storage.into_join(),
+        }
+    }
+
+    fn ancestors_with<S: IntoJoin>(
+        self,
+        id: EntityId,
+        storage: S,
+    ) -> WithIter<AncestorIter<C>, S::Join> {
+        WithIter {
+            iter: self.ancestors(id),
+            storage: storage.into_join(),
+        }
+    }
+
+    // the nearest ancestor that has a component in `storage`, along with it
+    fn find_ancestor_with<S: IntoJoin>(
+        self,
+        id: EntityId,
+        storage: S,
+    ) -> Option<(EntityId, <S::Join as JoinStorage>::Out)> {
+        self.ancestors_with(id, storage).next()
+    }
+
+    // depth first
+    fn descendants_with<S: IntoJoin>(
+        self,
+        id: EntityId,
+        storage: S,
+    ) -> WithIter<DescendantsDepthFirstIter<P, C>, S::Join> {
+        WithIter {
+            iter: self.descendants_depth_first(id),
+            storage: storage.into_join(),
+        }
+    }
+    // the first descendant matching `predicate`, depth first
+    fn find_descendant<F>(self, id: EntityId, mut predicate: F) -> Option<EntityId>
+    where
+        F: FnMut(EntityId) -> bool,
+    {
+        self.descendants_depth_first(id).find(|&id| predicate(id))
+    }
+
+    // the closest descendant matching `predicate`
+    fn find_descendant_breadth_first<F>(self, id: EntityId, mut predicate: F) -> Option<EntityId>
+    where
+        F: FnMut(EntityId) -> bool,
+    {
+        self.descendants_breadth_first(id).find(|&id| predicate(id))
+    }
+
+    fn find_child<F>(self, id: EntityId, mut predicate: F) -> Option<EntityId>
+    where
+        F: FnMut(EntityId) -> bool,
+    {
+        self.children(id).find(|&id| predicate(id))
+    }
+
+    // breadth first, so the closest ones come first
+    fn descendants_with_component<S: IntoJoin>(
+        self,
+        id: EntityId,
+        storage: S,
+    ) -> DescendantsWithComponentIter<P, C, S::Join> {
+        DescendantsWithComponentIter(WithIter {
+            iter: self.descendants_breadth_first(id),
+            storage: storage.into_join(),
+        })
+    }
+}
+
+impl<'a, P, C, T: 'a> HierarchyIter<'a, P, C, T> for (P, C)
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    fn storages(self) -> (P, C) {
+        self
+    }
 }
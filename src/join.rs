@@ -0,0 +1,122 @@
+use shipyard::iter::{AbstractMut, IntoAbstract};
+use shipyard::*;
+use std::collections::HashSet;
+
+// A user storage that hierarchy iterators can be joined with, see children_with() and friends
+// `&View<V>` and `&ViewMut<V>` join as `&V`
+// `&mut ViewMut<V>` as `&mut V`, or `Mut<V>` when V tracks modification so writes get flagged
+pub trait IntoJoin {
+    type Join: JoinStorage;
+
+    fn into_join(self) -> Self::Join;
+}
+
+pub trait JoinStorage {
+    type Out;
+
+    // None if `id` doesn't have the component
+    fn join(&mut self, id: EntityId) -> Option<Self::Out>;
+}
+
+impl<'a, 'v, V: Component> IntoJoin for &'a View<'v, V> {
+    type Join = Self;
+
+    fn into_join(self) -> Self::Join {
+        self
+    }
+}
+
+impl<'a, 'v, V: Component> JoinStorage for &'a View<'v, V> {
+    type Out = &'a V;
+
+    fn join(&mut self, id: EntityId) -> Option<Self::Out> {
+        self.get(id).ok()
+    }
+}
+
+impl<'a, 'v, V: Component> IntoJoin for &'a ViewMut<'v, V> {
+    type Join = Self;
+
+    fn into_join(self) -> Self::Join {
+        self
+    }
+}
+
+impl<'a, 'v, V: Component> JoinStorage for &'a ViewMut<'v, V> {
+    type Out = &'a V;
+
+    fn join(&mut self, id: EntityId) -> Option<Self::Out> {
+        self.get(id).ok()
+    }
+}
+
+impl<'a, 'v, V: Component> IntoJoin for &'a mut ViewMut<'v, V>
+where
+    &'a mut ViewMut<'v, V>: IntoAbstract,
+    <&'a mut ViewMut<'v, V> as IntoAbstract>::AbsView: AbstractMut<Index = usize>,
+{
+    type Join = JoinMut<<Self as IntoAbstract>::AbsView>;
+
+    fn into_join(self) -> Self::Join {
+        JoinMut {
+            window: self.into_abstract(),
+            joined: HashSet::new(),
+        }
+    }
+}
+
+// Hands out mutable borrows of the components of the entities it's asked for
+//
+// it goes through the same raw window shipyard's own iterators use:
+// every pointer is taken once from the `&mut ViewMut`, which stays borrowed for as long as the window lives,
+// and each component is reached by offsetting from them, like slice::IterMut does
+// the hierarchy iterators never yield an entity twice, but the indices handed out are still kept around
+// so that a corrupted hierarchy can't get two borrows of the same component
+// (for the same reason it isn't Clone, even though the window is)
+pub struct JoinMut<A> {
+    window: A,
+    joined: HashSet<usize>,
+}
+
+impl<A> JoinStorage for JoinMut<A>
+where
+    A: AbstractMut<Index = usize>,
+{
+    type Out = A::Out;
+
+    fn join(&mut self, id: EntityId) -> Option<Self::Out> {
+        let index = self.window.indices_of(id, 0, 0)?;
+        if !self.joined.insert(index) {
+            return None;
+        }
+
+        // SAFETY: `index` was just looked up in this window so it's in bounds,
+        // and it was never handed out before so no other borrow of its component exists
+        Some(unsafe { self.window.get_datas(index) })
+    }
+}
+
+// A hierarchy iterator joined with a user storage
+// entities without the component are skipped
+#[derive(Debug, Clone)]
+pub struct WithIter<I, J> {
+    pub iter: I,
+    pub storage: J,
+}
+
+impl<I, J> Iterator for WithIter<I, J>
+where
+    I: Iterator<Item = EntityId>,
+    J: JoinStorage,
+{
+    type Item = (EntityId, J::Out);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for id in self.iter.by_ref() {
+            if let Some(component) = self.storage.join(id) {
+                return Some((id, component));
+            }
+        }
+        None
+    }
+}
@@ -1,155 +1,793 @@
 //Mostly copy/paste from https://leudz.github.io/shipyard/book/recipes/hierarchy.html
+mod command;
 mod components;
 mod debug;
+mod deferred;
+mod edge;
+mod events;
 mod iter;
+mod join;
+mod links;
+#[cfg(feature = "parallel")]
+mod par_iter;
+mod path;
+mod propagate;
+#[cfg(feature = "serde")]
+mod value;
+mod view;
 
 use shipyard::*;
 
+pub use self::command::*;
 pub use self::components::*;
 pub use self::debug::*;
+pub use self::deferred::*;
+pub use self::edge::*;
+pub use self::events::*;
 pub use self::iter::*;
+pub use self::join::*;
+pub use self::links::*;
+#[cfg(feature = "parallel")]
+pub use self::par_iter::*;
+pub use self::path::*;
+pub use self::propagate::*;
+#[cfg(feature = "serde")]
+pub use self::value::*;
+pub use self::view::*;
 
 pub trait HierarchyMut<T> {
     // Attaches an entity as a child to a given parent entity.
     fn attach(&mut self, id: EntityId, parent: EntityId);
 
+    // Attaches an entity as the `index`th child of the given parent entity.
+    // An index past the end attaches it as the last child.
+    fn attach_at(&mut self, id: EntityId, parent: EntityId, index: usize);
+
     // Creates a new entity and attaches it to the given parent.
     fn attach_new(&mut self, parent: EntityId) -> EntityId;
 
+    // Attaches entities as the last children of `parent`, in order, repeated ids only once
+    // `parent` and its ancestors are skipped, they can't be its children
+    // the sibling ring is built in one go and the Parent component written once
+    fn attach_many(&mut self, parent: EntityId, ids: &[EntityId]);
+
+    // Creates `count` new entities and attaches them to the given parent.
+    fn attach_new_many(&mut self, parent: EntityId, count: usize) -> Vec<EntityId>;
+
+    // Detaches an entity from its parent, it becomes a root with its subtree intact
+    fn detach(&mut self, id: EntityId);
+
     // Removes an entity from the hierarchy
     fn remove_single(&mut self, id: EntityId);
 
+    // Removes an entity from the hierarchy, its children go where `policy` says
+    // returns false and does nothing if they'd be reparented to `id` or one of its descendants
+    fn remove_single_with(&mut self, id: EntityId, policy: ChildPolicy) -> bool;
+
     // Removes a subtree from the hierarchy
     fn remove(&mut self, id: EntityId);
 
+    // Stable sorts, siblings already in order aren't touched
     fn sort_children_by<F>(&mut self, id: EntityId, compare: F)
     where
         F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering;
+
+    fn sort_children_by_key<K, F>(&mut self, id: EntityId, key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K;
+
+    // calls `key` only once per child, for keys that are expensive to get
+    fn sort_children_by_cached_key<K, F>(&mut self, id: EntityId, key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K;
+
+    // sorts the children of `id`, and of every one of its descendants
+    fn sort_descendants_by<F>(&mut self, id: EntityId, compare: F)
+    where
+        F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering;
+
+    // Moves a child to the `index`th place among its siblings, an index past the end makes it the last child
+    fn move_child_to(&mut self, id: EntityId, index: usize);
+
+    // Exchanges the places of two children of the same parent, does nothing otherwise
+    fn swap_siblings(&mut self, a: EntityId, b: EntityId);
+
+    fn reverse_children(&mut self, parent: EntityId);
+
+    // Makes `id` the first child of its parent
+    fn bring_to_front(&mut self, id: EntityId);
+
+    // Makes `id` the last child of its parent
+    fn send_to_back(&mut self, id: EntityId);
+
+    // Replaces `id` by its children, in order, at the place it had among its siblings
+    // `id` is left as a root without children, if it had no parent its children become roots
+    fn ungroup(&mut self, id: EntityId);
+
+    // Wraps a run of consecutive siblings, given in order, in `new_parent` which takes their place
+    // they go after the children `new_parent` might already have
+    // returns false and does nothing if `ids` isn't such a run, `new_parent` is in one of their subtrees
+    // or `new_parent` is their parent or one of its ancestors
+    fn group(&mut self, ids: &[EntityId], new_parent: EntityId) -> bool;
+
+    // Makes `children` exactly the children of `parent`, in that order
+    // former children missing from the list are detached, the others are attached or moved as needed
+    // duplicates only count once, at their first place
+    // `parent` and its ancestors are skipped, they can't be its children
+    fn set_children(&mut self, parent: EntityId, children: &[EntityId]);
 }
 
-//the storages we'll impl Hierarchy on
-// 'a is the reference of the view you take to make HierarchyStorageMut
-// 'b is the scope of the system, that's the lifetime of the view
-pub type HierarchyStoragesMut<'a, 'b, T> = (
-    &'a mut EntitiesViewMut<'b>,
-    &'a mut ViewMut<'b, Parent<T>>,
-    &'a mut ViewMut<'b, Child<T>>,
-);
+// What happens to the children of an entity removed with remove_single_with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildPolicy {
+    // they become roots, like remove_single
+    Orphan,
+    // they take the removed entity's place among its siblings, in order
+    // roots if it had no parent
+    ReparentToGrandparent,
+    // they become the last children of the given entity, in order
+    // which can't be the removed entity or in its subtree
+    ReparentTo(EntityId),
+}
 
 // detach an entity from the hierarchy.
-// it's not on the trait since it's only for internal use
-// the public api is remove/remove_single
+// HierarchyMut::detach is the public version, this one doesn't record an event
 //
-// why 'static? Because I couldn't figure out the right lifetime stuff :P
-// It's not so bad though because components MUST own their data
-pub(crate) fn detach<T: 'static>(hierarchy: &mut HierarchyStoragesMut<T>, id: EntityId) {
-    let (_, parent_storage, child_storage) = hierarchy;
-
-    // remove the Child component - if nonexistent, do nothing
-    if let Some(child) = child_storage.remove(id) {
-        // retrieve and update Parent component from ancestor
-        let parent = &mut parent_storage[child.parent];
-        parent.num_children -= 1;
-
-        if parent.num_children == 0 {
-            // if the number of children is zero, the Parent component must be removed
-            parent_storage.remove(child.parent);
-        } else {
-            // the ancestor still has children, and we have to change some linking
-            // check if we have to change first_child
-            if parent.first_child == id {
-                parent.first_child = child.next;
-            }
-            // remove the detached child from the sibling chain
-            child_storage[child.prev].next = child.next;
-            child_storage[child.next].prev = child.prev;
+// the Child component (and the Parent, if it was the last child) is deleted rather than removed
+// so storages tracking deletion still have the old links around
+// (unless only removals are tracked, see HierarchyTracking::discard)
+//
+// returns the old parent, if there was one
+// recording an event is left to the caller, which knows what the detach is part of
+pub(crate) fn detach<T: HierarchyTag>(
+    hierarchy: &mut HierarchyViewMut<T>,
+    id: EntityId,
+) -> Option<EntityId> {
+    let HierarchyViewMut {
+        parent_storage,
+        child_storage,
+        ..
+    } = hierarchy;
+
+    // discard the Child component - if nonexistent, do nothing
+    let child = *child_storage.get(id).ok()?;
+    child_storage.discard(id);
+
+    // retrieve and update Parent component from ancestor
+    let parent = parent_storage.tracked_mut(child.parent);
+    parent.num_children -= 1;
+
+    if parent.num_children == 0 {
+        // if the number of children is zero, the Parent component must be deleted
+        parent_storage.discard(child.parent);
+    } else {
+        // the ancestor still has children, and we have to change some linking
+        // check if we have to change first_child
+        if parent.first_child == id {
+            parent.first_child = child.next;
+        }
+        // remove the detached child from the sibling chain
+        child_storage.tracked_mut(child.prev).next = child.next;
+        child_storage.tracked_mut(child.next).prev = child.prev;
+    }
+
+    Some(child.parent)
+}
+
+// the ids that can become children of `parent`, in order and without repeats
+// `parent` itself and its ancestors are left out, attaching them would close a cycle
+pub(crate) fn attachable<T: HierarchyTag>(
+    hierarchy: &HierarchyViewMut<T>,
+    parent: EntityId,
+    ids: &[EntityId],
+) -> Vec<EntityId> {
+    let mut skipped = hierarchy
+        .ancestors_inclusive(parent)
+        .collect::<std::collections::HashSet<_>>();
+    ids.iter()
+        .copied()
+        .filter(|id| skipped.insert(*id))
+        .collect()
+}
+
+// relink the sibling ring of a parent so that it follows the order of `children`
+// which must be exactly the current (non-empty) set of children, in any order
+//
+// only links that actually change are written, so tracking flags just what moved
+// returns whether anything changed
+pub(crate) fn relink<T: HierarchyTag>(
+    hierarchy: &mut HierarchyViewMut<T>,
+    parent: EntityId,
+    children: &[EntityId],
+) -> bool {
+    let HierarchyViewMut {
+        parent_storage,
+        child_storage,
+        ..
+    } = hierarchy;
+
+    let mut changed = false;
+
+    // set first_child in Parent component
+    if parent_storage[parent].first_child != children[0] {
+        parent_storage.tracked_mut(parent).first_child = children[0];
+        changed = true;
+    }
+
+    // loop through children (wrapping around at the end) and relink them
+    for (index, &id) in children.iter().enumerate() {
+        let prev = children[(index + children.len() - 1) % children.len()];
+        let next = children[(index + 1) % children.len()];
+        let child = &child_storage[id];
+        if child.prev != prev || child.next != next {
+            let child = child_storage.tracked_mut(id);
+            child.prev = prev;
+            child.next = next;
+            changed = true;
         }
     }
+
+    changed
 }
 
-impl<T: 'static> HierarchyMut<T> for HierarchyStoragesMut<'_, '_, T> {
+// the position of a child among its siblings
+fn sibling_index<T: HierarchyTag>(hierarchy: &HierarchyViewMut<T>, id: EntityId) -> usize {
+    let parent = hierarchy.child_storage[id].parent;
+    let mut cursor = hierarchy.parent_storage[parent].first_child;
+    let mut index = 0;
+    while cursor != id {
+        cursor = hierarchy.child_storage[cursor].next;
+        index += 1;
+    }
+    index
+}
+
+// move a child within its parent's sibling ring so it ends up `index`th
+// unlike detach/attach, the Child component stays, only the links around it are rewritten
+fn move_sibling<T: HierarchyTag>(
+    hierarchy: &mut HierarchyViewMut<T>,
+    id: EntityId,
+    index: usize,
+) -> bool {
+    let Some(child) = hierarchy.child_storage.get(id).ok().copied() else {
+        return false;
+    };
+    let num_children = hierarchy.parent_storage[child.parent].num_children;
+    let index = index.min(num_children - 1);
+    if sibling_index(hierarchy, id) == index {
+        return false;
+    }
+
+    let HierarchyViewMut {
+        parent_storage,
+        child_storage,
+        ..
+    } = hierarchy;
+
+    // take it out of the ring, there are at least two children if it's moving
+    let mut first_child = parent_storage[child.parent].first_child;
+    if first_child == id {
+        first_child = child.next;
+        parent_storage.tracked_mut(child.parent).first_child = first_child;
+    }
+    child_storage.tracked_mut(child.prev).next = child.next;
+    child_storage.tracked_mut(child.next).prev = child.prev;
+
+    // and put it back in front of whoever is at `index` among the others
+    // the ring wraps around, so the last place is in front of the first child
+    let mut next = first_child;
+    for _ in 0..(index % (num_children - 1)) {
+        next = child_storage[next].next;
+    }
+    let prev = child_storage[next].prev;
+    child_storage.tracked_mut(prev).next = id;
+    child_storage.tracked_mut(next).prev = id;
+    let moved = child_storage.tracked_mut(id);
+    moved.prev = prev;
+    moved.next = next;
+    if index == 0 {
+        parent_storage.tracked_mut(child.parent).first_child = id;
+    }
+
+    true
+}
+
+impl<T: HierarchyTag> HierarchyMut<T> for HierarchyViewMut<'_, T> {
     fn attach(&mut self, id: EntityId, parent: EntityId) {
-        detach(self, id);
+        self.attach_at(id, parent, usize::MAX);
+    }
 
-        let (entities, parent_storage, child_storage) = self;
+    fn attach_at(&mut self, id: EntityId, parent: EntityId, index: usize) {
         // the entity we want to attach might already be attached to another parent
+        if let Some(old_parent) = detach(self, id) {
+            self.record(HierarchyEvent::Detached { id, old_parent });
+        }
+
+        let HierarchyViewMut {
+            entities,
+            parent_storage,
+            child_storage,
+            ..
+        } = self;
 
         // either the designated parent already has a Parent component – and thus one or more children
-        if let Ok(mut p) = parent_storage.get(parent) {
-            // increase the parent's children counter
-            p.num_children += 1;
+        let index = if let Some(p) = parent_storage.try_tracked_mut(parent) {
+            let index = index.min(p.num_children);
 
             // get the ids of the new previous and next siblings of our new child
-            let prev = child_storage[p.first_child].prev;
-            let next = p.first_child;
+            // the sibling list is a ring, so inserting at the end is inserting before the first child
+            let mut next = p.first_child;
+            for _ in 0..(index % p.num_children) {
+                next = child_storage[next].next;
+            }
+            let prev = child_storage[next].prev;
+
+            // increase the parent's children counter
+            p.num_children += 1;
+            if index == 0 {
+                p.first_child = id;
+            }
 
             // change the linking
-            child_storage[prev].next = id;
-            child_storage[next].prev = id;
+            child_storage.tracked_mut(prev).next = id;
+            child_storage.tracked_mut(next).prev = id;
 
             // add the Child component to the new entity
-            entities.add_component(id, &mut **child_storage, Child::new(parent, prev, next));
+            entities.add_component(id, &mut *child_storage, Child::new(parent, prev, next));
+            index
         } else {
             // in this case our designated parent is missing a Parent component
             // we don't need to change any links, just insert both components
-            entities.add_component(id, &mut **child_storage, Child::new(parent, id, id));
-            entities.add_component(parent, &mut **parent_storage, Parent::new(1, id));
-        }
+            entities.add_component(id, &mut *child_storage, Child::new(parent, id, id));
+            entities.add_component(parent, &mut *parent_storage, Parent::new(1, id));
+            0
+        };
+
+        self.record(HierarchyEvent::Attached { id, parent, index });
     }
 
     fn attach_new(&mut self, parent: EntityId) -> EntityId {
-        let entities = &mut self.0;
-        let id = entities.add_entity((), ());
+        let id = self.entities.add_entity((), ());
         self.attach(id, parent);
         id
     }
 
+    fn attach_many(&mut self, parent: EntityId, ids: &[EntityId]) {
+        // a repeated id would be linked twice in the ring, only its first place counts
+        // and parent or its ancestors would close a cycle, they're left where they are
+        let ids = attachable(self, parent, ids);
+
+        let (Some(&first_id), Some(&last_id)) = (ids.first(), ids.last()) else {
+            return;
+        };
+
+        // only the ones already somewhere need detaching
+        for &id in &ids {
+            if let Some(old_parent) = detach(self, id) {
+                self.record(HierarchyEvent::Detached { id, old_parent });
+            }
+        }
+
+        let HierarchyViewMut {
+            entities,
+            parent_storage,
+            child_storage,
+            ..
+        } = self;
+
+        // the new run goes between the current last and first children, or loops on itself
+        let (first_index, before, after) = if let Some(p) = parent_storage.try_tracked_mut(parent) {
+            let first_index = p.num_children;
+            let first_child = p.first_child;
+            p.num_children += ids.len();
+            let last_child = child_storage[first_child].prev;
+            child_storage.tracked_mut(last_child).next = first_id;
+            child_storage.tracked_mut(first_child).prev = last_id;
+            (first_index, last_child, first_child)
+        } else {
+            entities.add_component(
+                parent,
+                &mut *parent_storage,
+                Parent::new(ids.len(), first_id),
+            );
+            (0, last_id, first_id)
+        };
+
+        for (index, &id) in ids.iter().enumerate() {
+            let prev = if index == 0 { before } else { ids[index - 1] };
+            let next = ids.get(index + 1).copied().unwrap_or(after);
+            entities.add_component(id, &mut *child_storage, Child::new(parent, prev, next));
+        }
+
+        for (index, &id) in ids.iter().enumerate() {
+            self.record(HierarchyEvent::Attached {
+                id,
+                parent,
+                index: first_index + index,
+            });
+        }
+    }
+
+    fn attach_new_many(&mut self, parent: EntityId, count: usize) -> Vec<EntityId> {
+        let ids = (0..count)
+            .map(|_| self.entities.add_entity((), ()))
+            .collect::<Vec<_>>();
+        self.attach_many(parent, &ids);
+        ids
+    }
+
+    fn detach(&mut self, id: EntityId) {
+        if let Some(old_parent) = detach(self, id) {
+            self.record(HierarchyEvent::Detached { id, old_parent });
+        }
+    }
+
     fn remove_single(&mut self, id: EntityId) {
         detach(self, id);
 
-        let (_, parent_storage, child_storage) = self;
-        let children = (&**parent_storage, &**child_storage)
-            .children(id)
-            .collect::<Vec<_>>();
+        let children = self.children(id).collect::<Vec<_>>();
         for child_id in children {
             detach(self, child_id);
+            self.record(HierarchyEvent::Detached {
+                id: child_id,
+                old_parent: id,
+            });
+        }
+
+        self.parent_storage.discard(id);
+        self.record(HierarchyEvent::Removed { root: id });
+    }
+
+    fn remove_single_with(&mut self, id: EntityId, policy: ChildPolicy) -> bool {
+        match policy {
+            ChildPolicy::Orphan => {}
+            ChildPolicy::ReparentToGrandparent => self.ungroup(id),
+            ChildPolicy::ReparentTo(parent) => {
+                if self
+                    .ancestors_inclusive(parent)
+                    .any(|ancestor| ancestor == id)
+                {
+                    return false;
+                }
+                let children = self.children(id).collect::<Vec<_>>();
+                self.attach_many(parent, &children);
+            }
         }
 
-        let parent_storage = &mut self.1;
-        parent_storage.remove(id);
+        self.remove_single(id);
+        true
     }
 
     fn remove(&mut self, id: EntityId) {
-        let (_, parent_storage, child_storage) = self;
-        for child_id in (&**parent_storage, &**child_storage)
-            .children(id)
-            .collect::<Vec<_>>()
-        {
+        // the whole subtree goes away as a single event
+        let events = self.events.take();
+        for child_id in self.children(id).collect::<Vec<_>>() {
             self.remove(child_id);
         }
         self.remove_single(id);
+        self.events = events;
+
+        self.record(HierarchyEvent::Removed { root: id });
     }
 
     fn sort_children_by<F>(&mut self, id: EntityId, mut compare: F)
     where
         F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering,
     {
-        let (_, parent_storage, child_storage) = self;
-        let mut children = (&**parent_storage, &**child_storage)
-            .children(id)
-            .collect::<Vec<EntityId>>();
+        let mut children = self.children(id).collect::<Vec<EntityId>>();
+        // also covers no or a single child
+        if children
+            .windows(2)
+            .all(|pair| compare(&pair[0], &pair[1]) != std::cmp::Ordering::Greater)
+        {
+            return;
+        }
+
+        children.sort_by(|a, b| compare(a, b));
+        if relink(self, id, &children) {
+            self.record(HierarchyEvent::Reordered { parent: id });
+        }
+    }
+
+    fn sort_children_by_key<K, F>(&mut self, id: EntityId, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K,
+    {
+        self.sort_children_by(id, |a, b| key(*a).cmp(&key(*b)));
+    }
+
+    fn sort_children_by_cached_key<K, F>(&mut self, id: EntityId, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(EntityId) -> K,
+    {
+        let mut children = self.children(id).collect::<Vec<EntityId>>();
         if children.len() > 1 {
-            children.sort_by(|a, b| compare(a, b));
-            // set first_child in Parent component
-            parent_storage[id].first_child = children[0];
-            // loop through children and relink them
-            for i in 0..children.len() - 1 {
-                child_storage[children[i]].next = children[i + 1];
-                child_storage[children[i + 1]].prev = children[i];
+            children.sort_by_cached_key(|child| key(*child));
+            if relink(self, id, &children) {
+                self.record(HierarchyEvent::Reordered { parent: id });
+            }
+        }
+    }
+
+    fn sort_descendants_by<F>(&mut self, id: EntityId, mut compare: F)
+    where
+        F: FnMut(&EntityId, &EntityId) -> std::cmp::Ordering,
+    {
+        // sorting doesn't change who has children, only in which order they're visited
+        let parents = std::iter::once(id)
+            .chain(self.descendants_depth_first(id))
+            .filter(|parent| self.parent_storage.contains(*parent))
+            .collect::<Vec<_>>();
+        for parent in parents {
+            self.sort_children_by(parent, &mut compare);
+        }
+    }
+
+    fn move_child_to(&mut self, id: EntityId, index: usize) {
+        if move_sibling(self, id, index) {
+            let parent = self.child_storage[id].parent;
+            self.record(HierarchyEvent::Reordered { parent });
+        }
+    }
+
+    fn swap_siblings(&mut self, a: EntityId, b: EntityId) {
+        let (Ok(child_a), Ok(child_b)) = (self.child_storage.get(a), self.child_storage.get(b))
+        else {
+            return;
+        };
+        if a == b || child_a.parent != child_b.parent {
+            return;
+        }
+        let parent = child_a.parent;
+
+        // move the later one to the earlier place first, it shifts the other one by one
+        let (index_a, index_b) = (sibling_index(self, a), sibling_index(self, b));
+        let ((first, first_index), (second, second_index)) = if index_a < index_b {
+            ((a, index_a), (b, index_b))
+        } else {
+            ((b, index_b), (a, index_a))
+        };
+        move_sibling(self, second, first_index);
+        move_sibling(self, first, second_index);
+
+        self.record(HierarchyEvent::Reordered { parent });
+    }
+
+    fn reverse_children(&mut self, parent: EntityId) {
+        let HierarchyViewMut {
+            parent_storage,
+            child_storage,
+            ..
+        } = self;
+
+        let Ok(&Parent {
+            num_children,
+            first_child,
+            ..
+        }) = parent_storage.get(parent)
+        else {
+            return;
+        };
+        if num_children < 2 {
+            return;
+        }
+
+        // the last child becomes the first and every link flips direction
+        parent_storage.tracked_mut(parent).first_child = child_storage[first_child].prev;
+        let mut cursor = first_child;
+        for _ in 0..num_children {
+            let child = child_storage[cursor];
+            // with two children prev and next are the same sibling
+            if child.prev != child.next {
+                let child = child_storage.tracked_mut(cursor);
+                std::mem::swap(&mut child.prev, &mut child.next);
             }
-            child_storage[children[0]].prev = *children.last().unwrap();
-            child_storage[*children.last().unwrap()].next = children[0];
+            cursor = child.next;
         }
+
+        self.record(HierarchyEvent::Reordered { parent });
+    }
+
+    fn ungroup(&mut self, id: EntityId) {
+        let children = self.children(id).collect::<Vec<_>>();
+        let Some(child) = self.child_storage.get(id).ok().copied() else {
+            for child_id in children {
+                self.detach(child_id);
+            }
+            return;
+        };
+        let grandparent = child.parent;
+        let Some((&first, &last)) = children.first().zip(children.last()) else {
+            self.detach(id);
+            return;
+        };
+        let index = sibling_index(self, id);
+
+        let HierarchyViewMut {
+            parent_storage,
+            child_storage,
+            ..
+        } = self;
+
+        // the children's own ring is spliced in where `id` was
+        let p = parent_storage.tracked_mut(grandparent);
+        if p.first_child == id {
+            p.first_child = first;
+        }
+        p.num_children += children.len() - 1;
+        if child.prev != id {
+            child_storage.tracked_mut(child.prev).next = first;
+            child_storage.tracked_mut(first).prev = child.prev;
+            child_storage.tracked_mut(child.next).prev = last;
+            child_storage.tracked_mut(last).next = child.next;
+        }
+        for &child_id in &children {
+            child_storage.tracked_mut(child_id).parent = grandparent;
+        }
+
+        child_storage.discard(id);
+        parent_storage.discard(id);
+
+        self.record(HierarchyEvent::Detached {
+            id,
+            old_parent: grandparent,
+        });
+        for (offset, child_id) in children.into_iter().enumerate() {
+            self.record(HierarchyEvent::Detached {
+                id: child_id,
+                old_parent: id,
+            });
+            self.record(HierarchyEvent::Attached {
+                id: child_id,
+                parent: grandparent,
+                index: index + offset,
+            });
+        }
+    }
+
+    fn group(&mut self, ids: &[EntityId], new_parent: EntityId) -> bool {
+        let Some((&first, &last)) = ids.first().zip(ids.last()) else {
+            return false;
+        };
+        let Ok(&Child { parent, .. }) = self.child_storage.get(first) else {
+            return false;
+        };
+        let first_child = self.parent_storage[parent].first_child;
+        let is_run = ids.windows(2).all(|pair| {
+            let child = &self.child_storage[pair[0]];
+            child.next == pair[1] && pair[1] != first_child
+        });
+        // new_parent can't come from the run's subtrees, nor be the run's parent or above it
+        if !is_run
+            || self
+                .ancestors_inclusive(new_parent)
+                .any(|ancestor| ids.contains(&ancestor))
+            || self
+                .ancestors_inclusive(parent)
+                .any(|ancestor| ancestor == new_parent)
+        {
+            return false;
+        }
+
+        self.detach(new_parent);
+        let index = sibling_index(self, first);
+
+        let HierarchyViewMut {
+            entities,
+            parent_storage,
+            child_storage,
+            ..
+        } = self;
+
+        // `new_parent` takes the place of the run
+        let before = child_storage[first].prev;
+        let after = child_storage[last].next;
+        let p = parent_storage.tracked_mut(parent);
+        if p.num_children == ids.len() {
+            p.first_child = new_parent;
+            p.num_children = 1;
+            entities.add_component(
+                new_parent,
+                &mut *child_storage,
+                Child::new(parent, new_parent, new_parent),
+            );
+        } else {
+            if p.first_child == first {
+                p.first_child = new_parent;
+            }
+            p.num_children -= ids.len() - 1;
+            child_storage.tracked_mut(before).next = new_parent;
+            child_storage.tracked_mut(after).prev = new_parent;
+            entities.add_component(
+                new_parent,
+                &mut *child_storage,
+                Child::new(parent, before, after),
+            );
+        }
+
+        // and the run is spliced at the end of its children, or closes on itself
+        let first_index = if let Some(np) = parent_storage.try_tracked_mut(new_parent) {
+            let first_index = np.num_children;
+            let first_child = np.first_child;
+            np.num_children += ids.len();
+            let last_child = child_storage[first_child].prev;
+            child_storage.tracked_mut(last_child).next = first;
+            child_storage.tracked_mut(first_child).prev = last;
+            child_storage.tracked_mut(first).prev = last_child;
+            child_storage.tracked_mut(last).next = first_child;
+            first_index
+        } else {
+            entities.add_component(
+                new_parent,
+                &mut *parent_storage,
+                Parent::new(ids.len(), first),
+            );
+            child_storage.tracked_mut(first).prev = last;
+            child_storage.tracked_mut(last).next = first;
+            0
+        };
+        for &id in ids {
+            child_storage.tracked_mut(id).parent = new_parent;
+        }
+
+        self.record(HierarchyEvent::Attached {
+            id: new_parent,
+            parent,
+            index,
+        });
+        for (offset, &id) in ids.iter().enumerate() {
+            self.record(HierarchyEvent::Detached {
+                id,
+                old_parent: parent,
+            });
+            self.record(HierarchyEvent::Attached {
+                id,
+                parent: new_parent,
+                index: first_index + offset,
+            });
+        }
+
+        true
+    }
+
+    fn set_children(&mut self, parent: EntityId, children: &[EntityId]) {
+        let children = attachable(self, parent, children);
+        let kept = children
+            .iter()
+            .copied()
+            .collect::<std::collections::HashSet<_>>();
+
+        for id in self.children(parent).collect::<Vec<_>>() {
+            if !kept.contains(&id) {
+                detach(self, id);
+                self.record(HierarchyEvent::Detached {
+                    id,
+                    old_parent: parent,
+                });
+            }
+        }
+
+        for &id in &children {
+            if self.ancestors(id).next() != Some(parent) {
+                self.attach(id, parent);
+            }
+        }
+
+        if !children.is_empty() && relink(self, parent, &children) {
+            self.record(HierarchyEvent::Reordered { parent });
+        }
+    }
+
+    fn bring_to_front(&mut self, id: EntityId) {
+        self.move_child_to(id, 0);
+    }
+
+    fn send_to_back(&mut self, id: EntityId) {
+        self.move_child_to(id, usize::MAX);
     }
 }
 
@@ -160,26 +798,19 @@ fn test_detach() {
     //The type of the hierarchy
     //not the type of the data in the hierarchy
     struct PlaceHolder {}
+    impl HierarchyTag for PlaceHolder {
+        type Tracking = track::Untracked;
+    }
 
-    let mut storages = world
-        .borrow::<(
-            EntitiesViewMut,
-            ViewMut<Parent<PlaceHolder>>,
-            ViewMut<Child<PlaceHolder>>,
-        )>()
-        .unwrap();
+    let mut hierarchy = world.borrow::<HierarchyViewMut<PlaceHolder>>().unwrap();
 
-    let root1 = storages.0.add_entity((), ());
-    let mut hierarchy = (&mut storages.0, &mut storages.1, &mut storages.2);
+    let root1 = hierarchy.entities.add_entity((), ());
     let e1 = hierarchy.attach_new(root1);
     let e2 = hierarchy.attach_new(e1);
 
     detach(&mut hierarchy, e1);
 
-    {
-        let storages = (&*hierarchy.1, &*hierarchy.2);
-        assert!(storages.descendants_depth_first(root1).eq(None));
-        assert!(storages.ancestors(e1).eq(None));
-        assert!(storages.children(e1).eq([e2].iter().cloned()));
-    }
+    assert!(hierarchy.descendants_depth_first(root1).eq(None));
+    assert!(hierarchy.ancestors(e1).eq(None));
+    assert!(hierarchy.children(e1).eq([e2].iter().cloned()));
 }
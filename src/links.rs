@@ -0,0 +1,375 @@
+use super::*;
+use shipyard::info::TypeInfo;
+use shipyard::*;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+// A directed acyclic relationship, for when one parent isn't enough
+// (shared material graphs, socket attachments, groups...)
+// it lives next to the tree components and doesn't touch them
+//
+// `Links<T>` is on the source and lists its targets in link order
+// `Backlinks<T>` is on the target and lists its sources
+// like Parent/Child, the components are deleted when their last link goes away
+pub struct Links<T> {
+    targets: Vec<EntityId>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Component for Links<T>
+where
+    T: HierarchyTag,
+{
+    type Tracking = T::Tracking;
+}
+
+impl<T> Links<T> {
+    pub fn targets(&self) -> &[EntityId] {
+        &self.targets
+    }
+}
+
+pub struct Backlinks<T> {
+    sources: Vec<EntityId>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Component for Backlinks<T>
+where
+    T: HierarchyTag,
+{
+    type Tracking = T::Tracking;
+}
+
+impl<T> Backlinks<T> {
+    pub fn sources(&self) -> &[EntityId] {
+        &self.sources
+    }
+}
+
+// Manual impls so that the marker type doesn't need to implement anything
+macro_rules! impl_link_traits {
+    ($name: ident, $field: ident) => {
+        impl<T> std::fmt::Debug for $name<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field(stringify!($field), &self.$field)
+                    .finish()
+            }
+        }
+
+        impl<T> Clone for $name<T> {
+            fn clone(&self) -> Self {
+                Self {
+                    $field: self.$field.clone(),
+                    marker: PhantomData,
+                }
+            }
+        }
+
+        impl<T> PartialEq for $name<T> {
+            fn eq(&self, other: &Self) -> bool {
+                self.$field == other.$field
+            }
+        }
+
+        impl<T> Eq for $name<T> {}
+    };
+}
+
+impl_link_traits!(Links, targets);
+impl_link_traits!(Backlinks, sources);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkError {
+    // `from` is reachable from `to` (or is `to`), linking them would close a cycle
+    Cycle { from: EntityId, to: EntityId },
+}
+
+impl std::fmt::Display for LinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LinkError::Cycle { from, to } => {
+                write!(f, "linking {:?} to {:?} would create a cycle", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+// Every entity reachable by following links from a starting entity, each visited once
+// depth-first, in link order, the starting entity excluded
+#[derive(Debug, Clone)]
+pub struct ReachableIter<L> {
+    pub link_storage: L,
+    pub stack: Vec<EntityId>,
+    pub visited: HashSet<EntityId>,
+}
+
+impl<'a, L, T: 'a> Iterator for ReachableIter<L>
+where
+    L: Get<Out = &'a Links<T>> + Copy,
+{
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(id) = self.stack.pop() {
+            if self.visited.insert(id) {
+                if let Ok(links) = self.link_storage.get(id) {
+                    self.stack.extend(links.targets.iter().rev());
+                }
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+pub trait LinksIter<'a, L, B, T: 'a>: Sized
+where
+    L: Get<Out = &'a Links<T>> + Copy,
+    B: Get<Out = &'a Backlinks<T>> + Copy,
+{
+    fn link_storages(self) -> (L, B);
+
+    // the targets of `id`, in link order
+    fn links(self, id: EntityId) -> std::iter::Copied<std::slice::Iter<'a, EntityId>> {
+        let (links, _) = self.link_storages();
+        links
+            .get(id)
+            .map(|links| links.targets.as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .copied()
+    }
+
+    // the sources linking to `id`, in the order they linked
+    fn backlinks(self, id: EntityId) -> std::iter::Copied<std::slice::Iter<'a, EntityId>> {
+        let (_, backlinks) = self.link_storages();
+        backlinks
+            .get(id)
+            .map(|backlinks| backlinks.sources.as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .copied()
+    }
+
+    fn reachable(self, id: EntityId) -> ReachableIter<L> {
+        let (links, _) = self.link_storages();
+        let mut visited = HashSet::new();
+        visited.insert(id);
+        ReachableIter {
+            link_storage: links,
+            stack: links
+                .get(id)
+                .map(|links| links.targets.iter().rev().copied().collect())
+                .unwrap_or_default(),
+            visited,
+        }
+    }
+
+    fn reaches(self, from: EntityId, to: EntityId) -> bool {
+        self.reachable(from).any(|id| id == to)
+    }
+}
+
+impl<'a, L, B, T: 'a> LinksIter<'a, L, B, T> for (L, B)
+where
+    L: Get<Out = &'a Links<T>> + Copy,
+    B: Get<Out = &'a Backlinks<T>> + Copy,
+{
+    fn link_storages(self) -> (L, B) {
+        self
+    }
+}
+
+// Borrow these like the hierarchy views, e.g. `world.borrow::<LinksViewMut<Materials>>()`
+// the mutable one only needs shared access to the entities
+pub struct LinksView<'v, T: HierarchyTag> {
+    pub link_storage: View<'v, Links<T>>,
+    pub backlink_storage: View<'v, Backlinks<T>>,
+}
+
+pub struct LinksViewMut<'v, T: HierarchyTag> {
+    pub entities: EntitiesView<'v>,
+    pub link_storage: ViewMut<'v, Links<T>>,
+    pub backlink_storage: ViewMut<'v, Backlinks<T>>,
+}
+
+pub struct LinksViewBorrower<T>(PhantomData<T>);
+pub struct LinksViewMutBorrower<T>(PhantomData<T>);
+
+impl<T: HierarchyTag> IntoBorrow for LinksView<'_, T> {
+    type Borrow = LinksViewBorrower<T>;
+}
+
+impl<'v, T: HierarchyTag> Borrow<'v> for LinksViewBorrower<T> {
+    type View = LinksView<'v, T>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(LinksView {
+            link_storage: <View<'v, Links<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            backlink_storage: <View<'v, Backlinks<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag> BorrowInfo for LinksView<'_, T> {
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <View<'_, Links<T>>>::borrow_info(info);
+        <View<'_, Backlinks<T>>>::borrow_info(info);
+    }
+}
+
+impl<T: HierarchyTag> IntoBorrow for LinksViewMut<'_, T> {
+    type Borrow = LinksViewMutBorrower<T>;
+}
+
+impl<'v, T: HierarchyTag> Borrow<'v> for LinksViewMutBorrower<T> {
+    type View = LinksViewMut<'v, T>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(LinksViewMut {
+            entities: <EntitiesView<'v> as IntoBorrow>::Borrow::borrow(world, last_run, current)?,
+            link_storage: <ViewMut<'v, Links<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            backlink_storage: <ViewMut<'v, Backlinks<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag> BorrowInfo for LinksViewMut<'_, T> {
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <EntitiesView<'_>>::borrow_info(info);
+        <ViewMut<'_, Links<T>>>::borrow_info(info);
+        <ViewMut<'_, Backlinks<T>>>::borrow_info(info);
+    }
+}
+
+impl<'a, 'v, T: HierarchyTag> LinksIter<'a, &'a View<'v, Links<T>>, &'a View<'v, Backlinks<T>>, T>
+    for &'a LinksView<'v, T>
+{
+    fn link_storages(self) -> (&'a View<'v, Links<T>>, &'a View<'v, Backlinks<T>>) {
+        (&self.link_storage, &self.backlink_storage)
+    }
+}
+
+impl<'a, 'v, T: HierarchyTag>
+    LinksIter<'a, &'a ViewMut<'v, Links<T>>, &'a ViewMut<'v, Backlinks<T>>, T>
+    for &'a LinksViewMut<'v, T>
+{
+    fn link_storages(self) -> (&'a ViewMut<'v, Links<T>>, &'a ViewMut<'v, Backlinks<T>>) {
+        (&self.link_storage, &self.backlink_storage)
+    }
+}
+
+pub trait LinksMut<T> {
+    // Links `from` to `to`, after any link `from` already has
+    // linking twice is a no-op, closing a cycle is an error and changes nothing
+    fn link(&mut self, from: EntityId, to: EntityId) -> Result<(), LinkError>;
+
+    // Removes the link from `from` to `to`, returns whether there was one
+    fn unlink(&mut self, from: EntityId, to: EntityId) -> bool;
+
+    // Removes every link to and from `id`, e.g. before deleting it
+    fn unlink_all(&mut self, id: EntityId);
+}
+
+impl<T: HierarchyTag> LinksMut<T> for LinksViewMut<'_, T> {
+    fn link(&mut self, from: EntityId, to: EntityId) -> Result<(), LinkError> {
+        if from == to || self.reaches(to, from) {
+            return Err(LinkError::Cycle { from, to });
+        }
+
+        let LinksViewMut {
+            entities,
+            link_storage,
+            backlink_storage,
+        } = self;
+
+        if let Some(links) = link_storage.try_tracked_mut(from) {
+            if links.targets.contains(&to) {
+                return Ok(());
+            }
+            links.targets.push(to);
+        } else {
+            entities.add_component(
+                from,
+                &mut *link_storage,
+                Links {
+                    targets: vec![to],
+                    marker: PhantomData,
+                },
+            );
+        }
+
+        if let Some(backlinks) = backlink_storage.try_tracked_mut(to) {
+            backlinks.sources.push(from);
+        } else {
+            entities.add_component(
+                to,
+                &mut *backlink_storage,
+                Backlinks {
+                    sources: vec![from],
+                    marker: PhantomData,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn unlink(&mut self, from: EntityId, to: EntityId) -> bool {
+        let LinksViewMut {
+            link_storage,
+            backlink_storage,
+            ..
+        } = self;
+
+        let linked = link_storage
+            .get(from)
+            .is_ok_and(|links| links.targets.contains(&to));
+        if !linked {
+            return false;
+        }
+
+        let links = link_storage.tracked_mut(from);
+        links.targets.retain(|&target| target != to);
+        if links.targets.is_empty() {
+            link_storage.discard(from);
+        }
+
+        let backlinks = backlink_storage.tracked_mut(to);
+        backlinks.sources.retain(|&source| source != from);
+        if backlinks.sources.is_empty() {
+            backlink_storage.discard(to);
+        }
+
+        true
+    }
+
+    fn unlink_all(&mut self, id: EntityId) {
+        for to in self.links(id).collect::<Vec<_>>() {
+            self.unlink(id, to);
+        }
+        for from in self.backlinks(id).collect::<Vec<_>>() {
+            self.unlink(from, id);
+        }
+    }
+}
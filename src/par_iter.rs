@@ -0,0 +1,69 @@
+use super::*;
+use rayon::prelude::*;
+use shipyard::*;
+
+// Parallel traversal, behind the `parallel` feature
+//
+// par_descendants splits work at subtrees: independent branches end up on different threads,
+// each branch is then walked depth first like descendants_depth_first.
+// The order of the items is unspecified.
+pub trait HierarchyParIter<'a, P, C, T: 'a>: HierarchyIter<'a, P, C, T>
+where
+    P: Get<Out = &'a Parent<T>> + Copy + Send + Sync + 'a,
+    C: Get<Out = &'a Child<T>> + Copy + Send + Sync + 'a,
+{
+    fn par_children(self, id: EntityId) -> rayon::vec::IntoIter<EntityId> {
+        self.children(id).collect::<Vec<_>>().into_par_iter()
+    }
+
+    fn par_descendants(self, id: EntityId) -> impl ParallelIterator<Item = EntityId> + 'a {
+        let storages = self.storages();
+
+        let roots = storages.children(id).map(Work::Subtree).collect::<Vec<_>>();
+
+        rayon::iter::split(roots, move |mut work| {
+            if work.len() > 1 {
+                let right = work.split_off(work.len() / 2);
+                (work, Some(right))
+            } else if let [Work::Subtree(root)] = work[..] {
+                // a single subtree is split into its root and its children's subtrees
+                let children = storages
+                    .children(root)
+                    .map(Work::Subtree)
+                    .collect::<Vec<_>>();
+                if children.is_empty() {
+                    (work, None)
+                } else {
+                    (vec![Work::Single(root)], Some(children))
+                }
+            } else {
+                (work, None)
+            }
+        })
+        .flat_map_iter(move |work| {
+            work.into_iter().flat_map(move |work| {
+                let (root, descendants) = match work {
+                    Work::Single(root) => (root, None),
+                    Work::Subtree(root) => (root, Some(storages.descendants_depth_first(root))),
+                };
+                std::iter::once(root).chain(descendants.into_iter().flatten())
+            })
+        })
+    }
+}
+
+impl<'a, H, P, C, T: 'a> HierarchyParIter<'a, P, C, T> for H
+where
+    H: HierarchyIter<'a, P, C, T>,
+    P: Get<Out = &'a Parent<T>> + Copy + Send + Sync + 'a,
+    C: Get<Out = &'a Child<T>> + Copy + Send + Sync + 'a,
+{
+}
+
+#[derive(Clone, Copy)]
+enum Work {
+    // just this entity
+    Single(EntityId),
+    // this entity and all of its descendants
+    Subtree(EntityId),
+}
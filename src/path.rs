@@ -0,0 +1,101 @@
+use super::*;
+use shipyard::*;
+use std::marker::PhantomData;
+
+// The name of a node within a hierarchy, used to address it by path e.g. "body/arm_l/hand"
+// siblings should have distinct names, lookups take the first match otherwise
+pub struct Name<T> {
+    pub name: String,
+    marker: PhantomData<T>,
+}
+
+impl<T> Component for Name<T>
+where
+    T: HierarchyTag,
+{
+    type Tracking = T::Tracking;
+}
+
+impl<T> Name<T> {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            marker: PhantomData,
+        }
+    }
+}
+
+// Manual impls so that the marker type doesn't need to implement anything
+impl<T> std::fmt::Debug for Name<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Name").field(&self.name).finish()
+    }
+}
+
+impl<T> Clone for Name<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.name.clone())
+    }
+}
+
+impl<T> PartialEq for Name<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl<T> Eq for Name<T> {}
+
+pub trait HierarchyPath<'a, P, C, T: 'a>: HierarchyIter<'a, P, C, T>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    // Resolves a '/' separated path relative to `id`
+    // ".." is the parent and "." (or an empty segment) stays in place
+    // None if a segment doesn't match anything, or goes above a root
+    fn find_path<'b, N>(self, id: EntityId, path: &str, names: N) -> Option<EntityId>
+    where
+        T: 'b,
+        N: Get<Out = &'b Name<T>> + Copy,
+    {
+        let storages = self.storages();
+        let mut current = id;
+        for segment in path.split('/') {
+            current = match segment {
+                "" | "." => current,
+                ".." => storages.ancestors(current).next()?,
+                segment => storages.find_child(current, |child| {
+                    names.get(child).is_ok_and(|name| name.name == segment)
+                })?,
+            };
+        }
+        Some(current)
+    }
+
+    // The path of `id` from its root, the root itself being ""
+    // so that `find_path(root, &path_of(id), names)` gets `id` back
+    // None if `id` or one of its ancestors (other than the root) doesn't have a name
+    fn path_of<'b, N>(self, id: EntityId, names: N) -> Option<String>
+    where
+        T: 'b,
+        N: Get<Out = &'b Name<T>> + Copy,
+    {
+        let mut segments = Vec::new();
+        let mut current = id;
+        for parent in self.ancestors(id) {
+            segments.push(names.get(current).ok()?.name.as_str());
+            current = parent;
+        }
+        segments.reverse();
+        Some(segments.join("/"))
+    }
+}
+
+impl<'a, H, P, C, T: 'a> HierarchyPath<'a, P, C, T> for H
+where
+    H: HierarchyIter<'a, P, C, T>,
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+}
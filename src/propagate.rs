@@ -0,0 +1,168 @@
+use super::*;
+use shipyard::*;
+
+// Local -> world style propagation, e.g. transforms
+//
+// Every entity with an `L` gets a `W`, computed by `combine` from its parent's `W` (None for roots) and its own `L`.
+// Parents are always computed before their children.
+// Entities without an `L` are skipped, along with everything under them.
+pub fn propagate<T, L, W, F>(
+    hierarchy: &HierarchyView<T>,
+    locals: &View<L>,
+    worlds: &mut ViewMut<W>,
+    combine: F,
+) where
+    T: HierarchyTag,
+    L: Component,
+    W: Component,
+    W::Tracking: HierarchyTracking,
+    F: FnMut(Option<&W>, &L) -> W,
+{
+    propagate_dirty(hierarchy, locals, worlds, |_| true, combine);
+}
+
+// Same as propagate, but only recomputes the subtrees under entities for which `is_dirty` returns true
+// e.g. `|id| locals.is_inserted_or_modified(id) || hierarchy.child_storage.is_inserted(id)`
+// entities which don't have a `W` yet are always computed
+pub fn propagate_dirty<T, L, W, D, F>(
+    hierarchy: &HierarchyView<T>,
+    locals: &View<L>,
+    worlds: &mut ViewMut<W>,
+    mut is_dirty: D,
+    mut combine: F,
+) where
+    T: HierarchyTag,
+    L: Component,
+    W: Component,
+    W::Tracking: HierarchyTracking,
+    D: FnMut(EntityId) -> bool,
+    F: FnMut(Option<&W>, &L) -> W,
+{
+    let roots = locals
+        .iter()
+        .ids()
+        .filter(|id| !hierarchy.child_storage.contains(*id))
+        .collect::<Vec<_>>();
+
+    for root in roots {
+        let root_dirty = is_dirty(root) || !worlds.contains(root);
+        if root_dirty {
+            let world = combine(None, &locals[root]);
+            write(worlds, root, world);
+        }
+
+        // depth of the dirty subtree we're in, if any
+        // the root is at depth 0, so a dirty root makes the whole tree dirty
+        let mut dirty_depth = root_dirty.then_some(0);
+        // depth of the subtree we're skipping because it's missing an L, if any
+        let mut skip_depth = None;
+
+        for (id, depth) in hierarchy.descendants_depth_first_with_depth(root) {
+            if skip_depth.is_some_and(|skip_depth| depth > skip_depth) {
+                continue;
+            }
+            skip_depth = None;
+
+            if dirty_depth.is_some_and(|dirty_depth| depth <= dirty_depth) {
+                dirty_depth = None;
+            }
+
+            let local = match locals.get(id) {
+                Ok(local) => local,
+                Err(_) => {
+                    skip_depth = Some(depth);
+                    continue;
+                }
+            };
+
+            if dirty_depth.is_none() && (is_dirty(id) || !worlds.contains(id)) {
+                dirty_depth = Some(depth);
+            }
+
+            if dirty_depth.is_some() {
+                let parent = hierarchy.child_storage[id].parent;
+                let world = combine(worlds.get(parent).ok(), local);
+                write(worlds, id, world);
+            }
+        }
+    }
+}
+
+fn write<W>(worlds: &mut ViewMut<W>, id: EntityId, world: W)
+where
+    W: Component,
+    W::Tracking: HierarchyTracking,
+{
+    if worlds.contains(id) {
+        *worlds.tracked_mut(id) = world;
+    } else {
+        worlds.add_component_unchecked(id, world);
+    }
+}
+
+// Top-down and bottom-up helpers over any user storage
+// e.g. inherited visibility going down, bounds or counts going up
+pub trait HierarchyPropagate<'a, P, C, T: 'a>: HierarchyIter<'a, P, C, T>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    // Calls `f(parent, child)` for every descendant of `root`, parents before children
+    // the root's own value is left as is
+    // entities without a `V` are skipped, along with everything under them
+    fn propagate_down<V, F>(self, root: EntityId, storage: &mut ViewMut<V>, mut f: F)
+    where
+        V: Component,
+        F: FnMut(&V, &mut V),
+    {
+        if !storage.contains(root) {
+            return;
+        }
+
+        let storages = self.storages();
+        let (_, child_storage) = storages;
+        let mut skip_depth = None;
+
+        for (id, depth) in storages.descendants_depth_first_with_depth(root) {
+            if skip_depth.is_some_and(|skip_depth| depth > skip_depth) {
+                continue;
+            }
+            skip_depth = None;
+
+            if !storage.contains(id) {
+                skip_depth = Some(depth);
+                continue;
+            }
+
+            let parent = child_storage.get(id).unwrap().parent;
+            storage.apply(id, parent, |child, parent| f(parent, child));
+        }
+    }
+
+    // Calls `f(parent, child)` for every descendant of `root`, in post-order
+    // so a child has received everything from its own children by the time it's passed to its parent
+    // pairs where either side lacks a `V` are skipped
+    fn aggregate_up<V, F>(self, root: EntityId, storage: &mut ViewMut<V>, mut f: F)
+    where
+        V: Component,
+        F: FnMut(&mut V, &V),
+    {
+        let storages = self.storages();
+        let (_, child_storage) = storages;
+
+        for id in storages.descendants_post_order(root) {
+            let parent = child_storage.get(id).unwrap().parent;
+            if storage.contains(id) && storage.contains(parent) {
+                storage.apply(parent, id, &mut f);
+            }
+        }
+    }
+}
+
+impl<'a, H, P, C, T: 'a> HierarchyPropagate<'a, P, C, T> for H
+where
+    H: HierarchyIter<'a, P, C, T>,
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+}
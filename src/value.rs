@@ -0,0 +1,52 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+use shipyard::*;
+
+// A plain nested snapshot of (part of) a hierarchy
+// meant for external tooling and snapshot tests, where the Debug output is too fragile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeValue {
+    pub id: EntityId,
+    pub label: String,
+    pub children: Vec<TreeValue>,
+}
+
+pub trait HierarchyIterValue<'a, P, C, T: 'a>: HierarchyIter<'a, P, C, T>
+where
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+    fn to_tree_value<F>(self, root: EntityId, get_label: F) -> TreeValue
+    where
+        F: Fn(EntityId) -> String,
+    {
+        fn build<'a, P, C, T: 'a>(
+            storages: (P, C),
+            id: EntityId,
+            get_label: &dyn Fn(EntityId) -> String,
+        ) -> TreeValue
+        where
+            P: Get<Out = &'a Parent<T>> + Copy,
+            C: Get<Out = &'a Child<T>> + Copy,
+        {
+            TreeValue {
+                id,
+                label: get_label(id),
+                children: storages
+                    .children(id)
+                    .map(|child| build(storages, child, get_label))
+                    .collect(),
+            }
+        }
+
+        build(self.storages(), root, &get_label)
+    }
+}
+
+impl<'a, H, P, C, T: 'a> HierarchyIterValue<'a, P, C, T> for H
+where
+    H: HierarchyIter<'a, P, C, T>,
+    P: Get<Out = &'a Parent<T>> + Copy,
+    C: Get<Out = &'a Child<T>> + Copy,
+{
+}
@@ -0,0 +1,117 @@
+use super::*;
+use shipyard::info::TypeInfo;
+use shipyard::*;
+use std::marker::PhantomData;
+
+// Borrow these directly, e.g. `world.borrow::<HierarchyViewMut<MyTree>>()`
+// or as a system parameter, instead of the individual storages
+pub struct HierarchyView<'v, T: HierarchyTag> {
+    pub parent_storage: View<'v, Parent<T>>,
+    pub child_storage: View<'v, Child<T>>,
+}
+
+pub struct HierarchyViewMut<'v, T: HierarchyTag> {
+    pub entities: EntitiesViewMut<'v>,
+    pub parent_storage: ViewMut<'v, Parent<T>>,
+    pub child_storage: ViewMut<'v, Child<T>>,
+    // None unless the World has a HierarchyEvents<T> unique
+    pub events: Option<UniqueViewMut<'v, HierarchyEvents<T>>>,
+}
+
+// the borrowers shouldn't have a lifetime, see shipyard's IntoBorrow docs
+pub struct HierarchyViewBorrower<T>(PhantomData<T>);
+pub struct HierarchyViewMutBorrower<T>(PhantomData<T>);
+
+impl<T: HierarchyTag> IntoBorrow for HierarchyView<'_, T> {
+    type Borrow = HierarchyViewBorrower<T>;
+}
+
+impl<'v, T: HierarchyTag> Borrow<'v> for HierarchyViewBorrower<T> {
+    type View = HierarchyView<'v, T>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(HierarchyView {
+            parent_storage: <View<'v, Parent<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            child_storage: <View<'v, Child<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag> BorrowInfo for HierarchyView<'_, T> {
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <View<'_, Parent<T>>>::borrow_info(info);
+        <View<'_, Child<T>>>::borrow_info(info);
+    }
+}
+
+impl<T: HierarchyTag> IntoBorrow for HierarchyViewMut<'_, T> {
+    type Borrow = HierarchyViewMutBorrower<T>;
+}
+
+impl<'v, T: HierarchyTag> Borrow<'v> for HierarchyViewMutBorrower<T> {
+    type View = HierarchyViewMut<'v, T>;
+
+    fn borrow(
+        world: &'v World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(HierarchyViewMut {
+            entities: <EntitiesViewMut<'v> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            parent_storage: <ViewMut<'v, Parent<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            child_storage: <ViewMut<'v, Child<T>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+            events: <Option<UniqueViewMut<'v, HierarchyEvents<T>>> as IntoBorrow>::Borrow::borrow(
+                world, last_run, current,
+            )?,
+        })
+    }
+}
+
+unsafe impl<T: HierarchyTag> BorrowInfo for HierarchyViewMut<'_, T> {
+    fn borrow_info(info: &mut Vec<TypeInfo>) {
+        <EntitiesViewMut<'_>>::borrow_info(info);
+        <ViewMut<'_, Parent<T>>>::borrow_info(info);
+        <ViewMut<'_, Child<T>>>::borrow_info(info);
+        <Option<UniqueViewMut<'_, HierarchyEvents<T>>>>::borrow_info(info);
+    }
+}
+
+impl<T: HierarchyTag> HierarchyViewMut<'_, T> {
+    pub(crate) fn record(&mut self, event: HierarchyEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+}
+
+// the iterators borrow the storages for as long as the reference to the view lives
+impl<'a, 'v, T: HierarchyTag> HierarchyIter<'a, &'a View<'v, Parent<T>>, &'a View<'v, Child<T>>, T>
+    for &'a HierarchyView<'v, T>
+{
+    fn storages(self) -> (&'a View<'v, Parent<T>>, &'a View<'v, Child<T>>) {
+        (&self.parent_storage, &self.child_storage)
+    }
+}
+
+impl<'a, 'v, T: HierarchyTag>
+    HierarchyIter<'a, &'a ViewMut<'v, Parent<T>>, &'a ViewMut<'v, Child<T>>, T>
+    for &'a HierarchyViewMut<'v, T>
+{
+    fn storages(self) -> (&'a ViewMut<'v, Parent<T>>, &'a ViewMut<'v, Child<T>>) {
+        (&self.parent_storage, &self.child_storage)
+    }
+}
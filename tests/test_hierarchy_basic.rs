@@ -6,22 +6,17 @@ mod tests {
     use std::collections::HashMap;
 
     struct MyTree {}
+    impl HierarchyTag for MyTree {
+        type Tracking = track::Untracked;
+    }
 
     #[test]
     fn test_hierarchy() {
         let world = World::new();
 
-        let mut storages = world
-            .borrow::<(
-                EntitiesViewMut,
-                ViewMut<Parent<MyTree>>,
-                ViewMut<Child<MyTree>>,
-            )>()
-            .unwrap();
-
-        let mut hierarchy = (&mut storages.0, &mut storages.1, &mut storages.2);
+        let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
 
-        let entities = &mut hierarchy.0;
+        let entities = &mut hierarchy.entities;
 
         let root1 = entities.add_entity((), ());
         let root2 = entities.add_entity((), ());
@@ -36,7 +31,7 @@ mod tests {
         let e5 = hierarchy.attach_new(e3);
 
         {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
+            let storages = &hierarchy;
 
             assert!(storages.children(e3).eq([e4, e5].iter().cloned()));
             assert!(storages.ancestors(e4).eq([e3, root2].iter().cloned()));
@@ -52,45 +47,1244 @@ mod tests {
         hierarchy.remove_single(e1);
 
         {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
+            let storages = &hierarchy;
             assert!(storages.children(e1).eq(None));
         }
 
         hierarchy.remove(root2);
 
         {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
+            let storages = &hierarchy;
             assert!(storages.descendants_depth_first(root2).eq(None));
             assert!(storages.descendants_depth_first(e3).eq(None));
             assert!(storages.ancestors(e5).eq(None));
         }
     }
 
+    #[test]
+    fn test_hierarchy_views_as_systems() {
+        let world = World::new();
+
+        let (root, e1, e2) = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            let root = hierarchy.entities.add_entity((), ());
+            let e1 = hierarchy.attach_new(root);
+            let e2 = hierarchy.attach_new(e1);
+            (root, e1, e2)
+        });
+
+        world.run(|hierarchy: HierarchyView<MyTree>| {
+            assert!(hierarchy.children(root).eq([e1].iter().cloned()));
+            assert!(hierarchy.ancestors(e2).eq([e1, root].iter().cloned()));
+        });
+
+        // the views can be borrowed alongside each other's storages as usual
+        let (hierarchy, parents) = world
+            .borrow::<(HierarchyView<MyTree>, View<Parent<MyTree>>)>()
+            .unwrap();
+        assert_eq!(parents[e1].first_child, e2);
+        assert!(hierarchy
+            .descendants_depth_first(root)
+            .eq([e1, e2].iter().cloned()));
+    }
+
+    #[test]
+    fn test_tracking() {
+        struct TrackedTree {}
+        impl HierarchyTag for TrackedTree {
+            type Tracking = track::All;
+        }
+
+        let world = World::new();
+
+        let (root, a, b) = world.run(|mut hierarchy: HierarchyViewMut<TrackedTree>| {
+            let root = hierarchy.entities.add_entity((), ());
+            let a = hierarchy.attach_new(root);
+            let b = hierarchy.attach_new(root);
+            (root, a, b)
+        });
+
+        world.run(|hierarchy: HierarchyViewMut<TrackedTree>| {
+            let HierarchyViewMut {
+                mut parent_storage,
+                mut child_storage,
+                ..
+            } = hierarchy;
+            assert!(parent_storage.is_inserted(root));
+            assert!(child_storage.is_inserted(a) && child_storage.is_inserted(b));
+
+            parent_storage.clear_all_removed_and_deleted();
+            parent_storage.clear_all_inserted_and_modified();
+            child_storage.clear_all_removed_and_deleted();
+            child_storage.clear_all_inserted_and_modified();
+        });
+
+        // reparent b under a
+        world.run(|mut hierarchy: HierarchyViewMut<TrackedTree>| hierarchy.attach(b, a));
+
+        world.run(|hierarchy: HierarchyView<TrackedTree>| {
+            let HierarchyView {
+                parent_storage,
+                child_storage,
+            } = hierarchy;
+
+            // b's old link is kept as a deletion, the new one is an insertion
+            assert!(child_storage
+                .deleted()
+                .eq([(b, &Child::new(root, a, a))].iter().cloned()));
+            assert!(child_storage.is_inserted(b));
+            assert_eq!(child_storage[b].parent, a);
+
+            // root lost a child, a became a parent and its sibling links changed
+            assert!(parent_storage.is_modified(root));
+            assert!(parent_storage.is_inserted(a));
+            assert!(child_storage.is_modified(a));
+        });
+
+        // sorting an already sorted list of children doesn't touch anything
+        world.run(|hierarchy: HierarchyViewMut<TrackedTree>| {
+            let HierarchyViewMut {
+                parent_storage,
+                child_storage,
+                ..
+            } = hierarchy;
+            parent_storage.clear_all_inserted_and_modified();
+            child_storage.clear_all_inserted_and_modified();
+        });
+
+        let c = world.run(|mut hierarchy: HierarchyViewMut<TrackedTree>| {
+            let c = hierarchy.attach_new(a);
+            let ids = hierarchy.children(a).collect::<Vec<_>>();
+            hierarchy.sort_children_by(a, |x, y| {
+                ids.iter()
+                    .position(|id| id == x)
+                    .cmp(&ids.iter().position(|id| id == y))
+            });
+            c
+        });
+
+        world.run(|hierarchy: HierarchyView<TrackedTree>| {
+            assert!(hierarchy.children(a).eq([b, c].iter().cloned()));
+            assert!(hierarchy
+                .parent_storage
+                .modified()
+                .iter()
+                .ids()
+                .eq([a].iter().cloned()));
+            assert!(hierarchy
+                .child_storage
+                .modified()
+                .iter()
+                .ids()
+                .eq([b].iter().cloned()));
+        });
+
+        // a hierarchy which only tracks removal sees its links go away as removals
+        struct RemovalTree {}
+        impl HierarchyTag for RemovalTree {
+            type Tracking = track::Removal;
+        }
+
+        let (root, a, b) = world.run(|mut hierarchy: HierarchyViewMut<RemovalTree>| {
+            let root = hierarchy.entities.add_entity((), ());
+            let a = hierarchy.attach_new(root);
+            let b = hierarchy.attach_new(a);
+            hierarchy.detach(a);
+            (root, a, b)
+        });
+
+        world.run(|hierarchy: HierarchyView<RemovalTree>| {
+            assert!(hierarchy.child_storage.removed().eq([a].iter().cloned()));
+            assert!(hierarchy
+                .parent_storage
+                .removed()
+                .eq([root].iter().cloned()));
+            assert!(hierarchy.children(a).eq([b].iter().cloned()));
+        });
+    }
+
+    #[test]
+    fn test_events() {
+        let world = World::new();
+
+        // nothing is recorded without the unique
+        let root = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            assert!(hierarchy.events.is_none());
+            let root = hierarchy.entities.add_entity((), ());
+            hierarchy.attach_new(root);
+            root
+        });
+
+        world.add_unique(HierarchyEvents::<MyTree>::new());
+
+        let (a, b, c, d) = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            let a = hierarchy.children(root).next().unwrap();
+            let b = hierarchy.attach_new(root);
+            let c = hierarchy.attach_new(b);
+            let d = hierarchy.attach_new(c);
+            hierarchy.attach(c, a);
+            (a, b, c, d)
+        });
+
+        world.run(|mut events: UniqueViewMut<HierarchyEvents<MyTree>>| {
+            assert!(events.drain().eq([
+                HierarchyEvent::Attached {
+                    id: b,
+                    parent: root,
+                    index: 1
+                },
+                HierarchyEvent::Attached {
+                    id: c,
+                    parent: b,
+                    index: 0
+                },
+                HierarchyEvent::Attached {
+                    id: d,
+                    parent: c,
+                    index: 0
+                },
+                HierarchyEvent::Detached {
+                    id: c,
+                    old_parent: b
+                },
+                HierarchyEvent::Attached {
+                    id: c,
+                    parent: a,
+                    index: 0
+                },
+            ]));
+            assert!(events.is_empty());
+        });
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            // already in order, nothing to report
+            hierarchy.sort_children_by(root, |x, y| x.cmp(y));
+            hierarchy.sort_children_by(root, |x, y| y.cmp(x));
+            hierarchy.remove_single(c);
+            hierarchy.remove(root);
+        });
+
+        world.run(|events: UniqueView<HierarchyEvents<MyTree>>| {
+            assert!(events.iter().cloned().eq([
+                HierarchyEvent::Reordered { parent: root },
+                HierarchyEvent::Detached {
+                    id: d,
+                    old_parent: c
+                },
+                HierarchyEvent::Removed { root: c },
+                HierarchyEvent::Removed { root },
+            ]));
+        });
+    }
+
+    #[test]
+    fn test_deferred_commands() {
+        fn build(swap_systems: bool) -> String {
+            let world = World::new();
+            world.add_unique(HierarchyCommands::<MyTree>::new());
+
+            let (root, a, b) = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+                let root = hierarchy.entities.add_entity((), ());
+                let a = hierarchy.attach_new(root);
+                let b = hierarchy.attach_new(root);
+                (root, a, b)
+            });
+
+            let spawn = |mut commands: HierarchyCommandsView<MyTree>| {
+                let c = commands.attach_new(a);
+                [c, commands.attach_new(c), commands.attach_new(c)]
+            };
+            let reparent = |mut commands: HierarchyCommandsView<MyTree>| {
+                commands.attach(a, b);
+                commands.sort_children_by(root, |x: &EntityId, y: &EntityId| y.cmp(x));
+            };
+
+            // only differing by the entity the new one goes under
+            let adopt = |parent: EntityId| {
+                move |mut commands: HierarchyCommandsView<MyTree>| {
+                    let n = commands.attach_new(root);
+                    commands.attach(n, parent);
+                }
+            };
+
+            let reserved = if swap_systems {
+                world.run(adopt(b));
+                world.run(adopt(a));
+                world.run(reparent);
+                world.run(spawn)
+            } else {
+                let reserved = world.run(spawn);
+                world.run(reparent);
+                world.run(adopt(a));
+                world.run(adopt(b));
+                reserved
+            };
+
+            // nothing happens until the flush
+            world.run(|hierarchy: HierarchyView<MyTree>| {
+                assert!(hierarchy
+                    .descendants_depth_first(root)
+                    .eq([a, b].iter().cloned()));
+            });
+
+            world.run(flush_hierarchy_commands::<MyTree>);
+
+            let commands = world
+                .borrow::<UniqueView<HierarchyCommands<MyTree>>>()
+                .unwrap();
+            assert!(commands.is_empty());
+            // the reservations are now real entities
+            let [c, d, e] = reserved.map(|reserved| commands.resolved(reserved).unwrap());
+            world.run(|hierarchy: HierarchyView<MyTree>| {
+                assert_eq!(hierarchy.children(a).last(), Some(c));
+                assert!(hierarchy.children(c).eq([d, e]));
+            });
+
+            let hierarchy = world.borrow::<HierarchyView<MyTree>>().unwrap();
+            hierarchy
+                .display_tree(root, |e| e.index().to_string())
+                .to_string()
+        }
+
+        assert_eq!(
+            build(false),
+            "0\n└── 2\n    ├── 1\n    │   ├── 3\n    │   └── 5\n    │       ├── 6\n    │       └── 7\n    └── 4\n"
+        );
+        assert_eq!(build(false), build(true));
+    }
+
+    #[test]
+    fn test_deferred_commands_order() {
+        fn build(swap_systems: bool) -> Vec<EntityId> {
+            let world = World::new();
+            world.add_unique(HierarchyCommands::<MyTree>::new());
+
+            let root = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+                let root = hierarchy.entities.add_entity((), ());
+                hierarchy.attach_new_many(root, 3);
+                root
+            });
+
+            // same content, only the comparators differ
+            let ascending = |mut commands: HierarchyCommandsView<MyTree>| {
+                commands.set_order(1);
+                commands.sort_children_by(root, |x: &EntityId, y: &EntityId| x.cmp(y));
+            };
+            let descending = |mut commands: HierarchyCommandsView<MyTree>| {
+                commands.sort_children_by(root, |x: &EntityId, y: &EntityId| y.cmp(x));
+            };
+
+            if swap_systems {
+                world.run(ascending);
+                world.run(descending);
+            } else {
+                world.run(descending);
+                world.run(ascending);
+            }
+            world.run(flush_hierarchy_commands::<MyTree>);
+
+            world.run(|hierarchy: HierarchyView<MyTree>| hierarchy.children(root).collect())
+        }
+
+        let children = build(false);
+        assert!(children.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(children, build(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "a ReservedEntity can only be used")]
+    fn test_deferred_commands_foreign_reservation() {
+        let mut world = World::new();
+        world.add_unique(HierarchyCommands::<MyTree>::new());
+        let root = world.add_entity(());
+
+        let reserved =
+            world.run(|mut commands: HierarchyCommandsView<MyTree>| commands.attach_new(root));
+        world.run(|mut commands: HierarchyCommandsView<MyTree>| {
+            commands.attach_new(reserved);
+        });
+    }
+
+    #[test]
+    fn test_propagate() {
+        #[derive(Debug, PartialEq)]
+        struct Local(i32);
+        impl Component for Local {
+            type Tracking = track::Untracked;
+        }
+        #[derive(Debug, PartialEq)]
+        struct Global(i32);
+        impl Component for Global {
+            type Tracking = track::Modification;
+        }
+
+        let world = World::new();
+
+        let (root, a, b, c, d, lone) = world.run(
+            |mut hierarchy: HierarchyViewMut<MyTree>, mut locals: ViewMut<Local>| {
+                let root = hierarchy.entities.add_entity(&mut locals, Local(1));
+                let a = hierarchy.attach_new(root);
+                let b = hierarchy.attach_new(a);
+                let c = hierarchy.attach_new(root);
+                let d = hierarchy.attach_new(c);
+                let lone = hierarchy.entities.add_entity(&mut locals, Local(7));
+                for (id, local) in [(a, 2), (b, 3), (d, 4)] {
+                    hierarchy
+                        .entities
+                        .add_component(id, &mut locals, Local(local));
+                }
+                (root, a, b, c, d, lone)
+            },
+        );
+
+        let combine = |parent: Option<&Global>, local: &Local| {
+            Global(parent.map_or(0, |parent| parent.0) + local.0)
+        };
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             locals: View<Local>,
+             mut globals: ViewMut<Global>| {
+                propagate(&hierarchy, &locals, &mut globals, combine);
+
+                assert_eq!(globals[root], Global(1));
+                assert_eq!(globals[a], Global(3));
+                assert_eq!(globals[b], Global(6));
+                assert_eq!(globals[lone], Global(7));
+                // c has no Local, so neither it nor d get anything
+                assert!(!globals.contains(c) && !globals.contains(d));
+            },
+        );
+
+        world.run(|mut locals: ViewMut<Local>, globals: ViewMut<Global>| {
+            locals[a].0 = 10;
+            locals[root].0 = 100;
+            globals.clear_all_modified();
+        });
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             locals: View<Local>,
+             mut globals: ViewMut<Global>| {
+                propagate_dirty(&hierarchy, &locals, &mut globals, |id| id == a, combine);
+
+                // only a's subtree was recomputed
+                assert_eq!(globals[root], Global(1));
+                assert_eq!(globals[a], Global(11));
+                assert_eq!(globals[b], Global(14));
+                assert!(globals.modified().iter().ids().eq([a, b].iter().cloned()));
+
+                // a dirty root takes its whole tree along
+                propagate_dirty(&hierarchy, &locals, &mut globals, |id| id == root, combine);
+                assert_eq!(globals[root], Global(100));
+                assert_eq!(globals[a], Global(110));
+                assert_eq!(globals[b], Global(113));
+                assert_eq!(globals[lone], Global(7));
+            },
+        );
+    }
+
+    #[test]
+    fn test_propagate_down_aggregate_up() {
+        struct Visible(bool);
+        impl Component for Visible {
+            type Tracking = track::Untracked;
+        }
+        struct Count(usize);
+        impl Component for Count {
+            type Tracking = track::Untracked;
+        }
+
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, l, m, n), _) = create_world_tree();
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             entities: EntitiesView,
+             mut visible: ViewMut<Visible>,
+             mut counts: ViewMut<Count>| {
+                for id in [root, a, b, c, d, e, f, g, h, i, j, k, l, m, n] {
+                    entities.add_component(id, &mut visible, Visible(id != d && id != g));
+                    entities.add_component(id, &mut counts, Count(1));
+                }
+                // j's subtree doesn't take part in counting
+                counts.remove(j);
+
+                hierarchy.propagate_down(root, &mut visible, |parent, child| child.0 &= parent.0);
+                let hidden = [root, a, b, c, d, e, f, g, h, i, j, k, l, m, n]
+                    .into_iter()
+                    .filter(|id| !visible[*id].0)
+                    .collect::<Vec<_>>();
+                assert_eq!(hidden, vec![d, g, h, i, j, k, l, m, n]);
+
+                hierarchy.aggregate_up(root, &mut counts, |parent, child| parent.0 += child.0);
+                assert_eq!(counts[root].0, 12);
+                assert_eq!(counts[a].0, 6);
+                assert_eq!(counts[g].0, 2);
+                assert_eq!(counts[m].0, 2);
+            },
+        );
+    }
+
+    #[test]
+    fn test_iter_with_components() {
+        struct Depth(usize);
+        impl Component for Depth {
+            type Tracking = track::Modification;
+        }
+
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, l, m, n), _) = create_world_tree();
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             entities: EntitiesView,
+             mut depths: ViewMut<Depth>| {
+                for id in [root, a, c, d, e, f, g, h, i, j, k, l, m, n] {
+                    entities.add_component(id, &mut depths, Depth(0));
+                }
+
+                // b has no Depth
+                assert!(hierarchy
+                    .children_with(root, &depths)
+                    .map(|(id, _)| id)
+                    .eq([a, c]));
+
+                for (id, mut depth) in hierarchy.descendants_with(root, &mut depths) {
+                    depth.0 = hierarchy.ancestors(id).count();
+                }
+                for (_, mut depth) in hierarchy.children_with(g, &mut depths) {
+                    depth.0 *= 10;
+                }
+                assert!(hierarchy
+                    .ancestors_with(n, &depths)
+                    .map(|(id, depth)| (id, depth.0))
+                    .eq([(m, 4), (j, 30), (g, 2), (c, 1), (root, 0)]));
+                assert_eq!(depths.modified().iter().count(), 13);
+
+                // the borrows handed out are all alive at once
+                let mut all = hierarchy
+                    .descendants_with(c, &mut depths)
+                    .collect::<Vec<_>>();
+                for (_, depth) in &mut all {
+                    depth.0 += 1;
+                }
+                assert!(all.iter().map(|(id, _)| *id).eq([f, g, j, m, n, k]));
+
+                // and never twice for the same entity
+                let mut join = (&mut depths).into_join();
+                assert!(join.join(a).is_some());
+                assert!(join.join(a).is_none());
+                assert!(join.join(b).is_none());
+            },
+        );
+
+        // untracked storages hand out plain `&mut`
+        struct Hidden(bool);
+        impl Component for Hidden {
+            type Tracking = track::Untracked;
+        }
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             entities: EntitiesView,
+             mut hidden: ViewMut<Hidden>| {
+                for id in [a, b, c] {
+                    entities.add_component(id, &mut hidden, Hidden(false));
+                }
+                for (_, hidden) in hierarchy.children_with(root, &mut hidden) {
+                    hidden.0 = true;
+                }
+                assert!(hidden.iter().all(|hidden| hidden.0));
+            },
+        );
+    }
+
+    #[test]
+    fn test_ancestor_queries() {
+        struct Canvas;
+        impl Component for Canvas {
+            type Tracking = track::Untracked;
+        }
+
+        let (world, (root, _, _, c, _, _, _, g, _, _, j, _, _, m, n), _) = create_world_tree();
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             entities: EntitiesView,
+             mut canvases: ViewMut<Canvas>| {
+                entities.add_component(root, &mut canvases, Canvas);
+                entities.add_component(g, &mut canvases, Canvas);
+
+                assert!(hierarchy.ancestors_inclusive(m).eq([m, j, g, c, root]));
+                assert!(hierarchy.ancestors_inclusive(root).eq([root]));
+
+                assert!(hierarchy
+                    .ancestors_until(n, |id| canvases.contains(id))
+                    .eq([m, j, g]));
+                assert!(hierarchy
+                    .ancestors_until(n, |_| false)
+                    .eq([m, j, g, c, root]));
+
+                assert_eq!(
+                    hierarchy.find_ancestor_with(n, &canvases).map(|(id, _)| id),
+                    Some(g)
+                );
+                assert_eq!(
+                    hierarchy.find_ancestor_with(g, &canvases).map(|(id, _)| id),
+                    Some(root)
+                );
+                assert!(hierarchy.find_ancestor_with(root, &canvases).is_none());
+            },
+        );
+    }
+
+    #[test]
+    fn test_find_descendants() {
+        struct Bone(&'static str);
+        impl Component for Bone {
+            type Tracking = track::Untracked;
+        }
+
+        let (world, (root, a, b, c, d, _, f, g, h, _, j, k, _, _, n), _) = create_world_tree();
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>, entities: EntitiesView, mut bones: ViewMut<Bone>| {
+                for (id, name) in [(h, "hand_r"), (k, "hand_r"), (n, "hand_r"), (j, "arm_r")] {
+                    entities.add_component(id, &mut bones, Bone(name));
+                }
+                let is_hand = |id| bones.get(id).is_ok_and(|bone| bone.0 == "hand_r");
+
+                // depth first finds the leftmost, breadth first the closest
+                assert_eq!(hierarchy.find_descendant(root, is_hand), Some(h));
+                assert_eq!(hierarchy.find_descendant(c, is_hand), Some(n));
+                assert_eq!(hierarchy.find_descendant_breadth_first(c, is_hand), Some(k));
+                assert_eq!(hierarchy.find_descendant(b, is_hand), None);
+
+                assert_eq!(hierarchy.find_child(root, |id| id == c), Some(c));
+                assert_eq!(hierarchy.find_child(root, |id| id == d), None);
+                assert_eq!(hierarchy.find_child(g, is_hand), Some(k));
+
+                assert!(hierarchy
+                    .descendants_with_component(root, &bones)
+                    .eq([h, j, k, n]));
+                assert!(hierarchy.descendants_with_component(a, &bones).eq([h]));
+                assert!(hierarchy.descendants_with_component(f, &bones).eq(None));
+            },
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let (world, (root, a, b, _, d, e, _, _, h, _, _, _, _, _, _), labels) = create_world_tree();
+
+        world.run(
+            |hierarchy: HierarchyView<MyTree>,
+             entities: EntitiesView,
+             mut names: ViewMut<Name<MyTree>>| {
+                for (id, label) in &labels {
+                    entities.add_component(*id, &mut names, Name::new(*label));
+                }
+
+                assert_eq!(hierarchy.find_path(root, "a/d/h", &names), Some(h));
+                assert_eq!(hierarchy.find_path(root, "a/d/h/", &names), Some(h));
+                assert_eq!(hierarchy.find_path(h, "../../e", &names), Some(e));
+                assert_eq!(hierarchy.find_path(h, "./../..", &names), Some(a));
+                assert_eq!(hierarchy.find_path(d, "", &names), Some(d));
+                assert_eq!(hierarchy.find_path(root, "a/x", &names), None);
+                assert_eq!(hierarchy.find_path(root, "..", &names), None);
+                // only children are matched, not deeper descendants
+                assert_eq!(hierarchy.find_path(root, "d", &names), None);
+
+                assert_eq!(hierarchy.path_of(h, &names).as_deref(), Some("a/d/h"));
+                assert_eq!(hierarchy.path_of(root, &names).as_deref(), Some(""));
+                for id in labels.keys() {
+                    let path = hierarchy.path_of(*id, &names).unwrap();
+                    assert_eq!(hierarchy.find_path(root, &path, &names), Some(*id));
+                }
+
+                names.delete(a);
+                assert_eq!(hierarchy.path_of(h, &names), None);
+                assert_eq!(hierarchy.path_of(b, &names).as_deref(), Some("b"));
+            },
+        );
+    }
+
+    #[test]
+    fn test_sorting_helpers() {
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, l, m, n), labels) = create_world_tree();
+        world.add_unique(HierarchyEvents::<MyTree>::new());
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            let mut calls = 0;
+            hierarchy.sort_children_by_cached_key(root, |id| {
+                calls += 1;
+                std::cmp::Reverse(labels[&id])
+            });
+            assert_eq!(calls, 3);
+            assert!(hierarchy.children(root).eq([c, b, a]));
+
+            // ties keep their order
+            hierarchy.sort_children_by_key(root, |id| id == b);
+            assert!(hierarchy.children(root).eq([c, a, b]));
+
+            hierarchy.sort_descendants_by(root, |x, y| labels[y].cmp(labels[x]));
+            assert!(hierarchy
+                .descendants_depth_first(root)
+                .eq([c, g, k, j, m, n, f, b, a, e, d, i, h, l]));
+        });
+
+        world.run(|mut events: UniqueViewMut<HierarchyEvents<MyTree>>| {
+            assert!(events.drain().eq([
+                HierarchyEvent::Reordered { parent: root },
+                HierarchyEvent::Reordered { parent: root },
+                HierarchyEvent::Reordered { parent: root },
+                HierarchyEvent::Reordered { parent: c },
+                HierarchyEvent::Reordered { parent: g },
+                HierarchyEvent::Reordered { parent: a },
+                HierarchyEvent::Reordered { parent: d },
+            ]));
+        });
+
+        // already sorted, nothing is relinked
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            hierarchy.sort_descendants_by(root, |x, y| labels[y].cmp(labels[x]));
+            hierarchy.sort_children_by_key(g, |id| labels[&id] != "k");
+            hierarchy.sort_children_by_cached_key(f, |id| labels[&id]);
+            assert!(hierarchy.events.as_ref().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_child_order_primitives() {
+        struct TrackedTree {}
+        impl HierarchyTag for TrackedTree {
+            type Tracking = track::Modification;
+        }
+
+        fn check(hierarchy: &HierarchyViewMut<TrackedTree>, parent: EntityId, order: &[EntityId]) {
+            assert!(hierarchy.children(parent).eq(order.iter().cloned()));
+            // the ring has to hold together both ways
+            for (index, id) in order.iter().enumerate() {
+                let child = hierarchy.child_storage[*id];
+                assert_eq!(child.next, order[(index + 1) % order.len()]);
+                assert_eq!(child.prev, order[(index + order.len() - 1) % order.len()]);
+            }
+        }
+
+        let world = World::new();
+
+        let (root, a, e, f) = world.run(|mut hierarchy: HierarchyViewMut<TrackedTree>| {
+            let root = hierarchy.entities.add_entity((), ());
+            let [a, b, c, d, e] = [(); 5].map(|_| hierarchy.attach_new(root));
+
+            hierarchy.move_child_to(a, 2);
+            check(&hierarchy, root, &[b, c, a, d, e]);
+            hierarchy.move_child_to(c, 100);
+            check(&hierarchy, root, &[b, a, d, e, c]);
+            hierarchy.move_child_to(e, 0);
+            check(&hierarchy, root, &[e, b, a, d, c]);
+
+            hierarchy.swap_siblings(e, c);
+            check(&hierarchy, root, &[c, b, a, d, e]);
+            hierarchy.swap_siblings(d, a);
+            check(&hierarchy, root, &[c, b, d, a, e]);
+            // not siblings
+            hierarchy.swap_siblings(a, root);
+            check(&hierarchy, root, &[c, b, d, a, e]);
+
+            hierarchy.reverse_children(root);
+            check(&hierarchy, root, &[e, a, d, b, c]);
+
+            hierarchy.bring_to_front(d);
+            check(&hierarchy, root, &[d, e, a, b, c]);
+            hierarchy.send_to_back(e);
+            check(&hierarchy, root, &[d, a, b, c, e]);
+
+            hierarchy.remove(b);
+            hierarchy.remove(c);
+            hierarchy.remove(d);
+            hierarchy.reverse_children(root);
+            check(&hierarchy, root, &[e, a]);
+            hierarchy.swap_siblings(e, a);
+            check(&hierarchy, root, &[a, e]);
+
+            let f = hierarchy.attach_new(root);
+            hierarchy.move_child_to(a, 1);
+            (root, a, e, f)
+        });
+
+        world.run(
+            |parent_storage: ViewMut<Parent<TrackedTree>>,
+             child_storage: ViewMut<Child<TrackedTree>>| {
+                parent_storage.clear_all_modified();
+                child_storage.clear_all_modified();
+            },
+        );
+        world.add_unique(HierarchyEvents::<TrackedTree>::new());
+
+        world.run(|mut hierarchy: HierarchyViewMut<TrackedTree>| {
+            // only the links around the moved child change
+            hierarchy.move_child_to(f, 0);
+            check(&hierarchy, root, &[f, e, a]);
+            assert!(hierarchy.parent_storage.is_modified(root));
+            let mut modified = hierarchy
+                .child_storage
+                .modified()
+                .iter()
+                .ids()
+                .collect::<Vec<_>>();
+            modified.sort();
+            let mut expected = vec![e, a, f];
+            expected.sort();
+            assert_eq!(modified, expected);
+
+            // already in place
+            hierarchy.move_child_to(a, 2);
+            hierarchy.bring_to_front(f);
+            hierarchy.send_to_back(a);
+            hierarchy.reverse_children(f);
+            assert!(hierarchy
+                .events
+                .as_ref()
+                .unwrap()
+                .iter()
+                .eq(&[HierarchyEvent::Reordered { parent: root }]));
+        });
+    }
+
+    #[test]
+    fn test_set_children() {
+        let (world, (root, a, b, c, d, e, f, g, h, i, ..), _) = create_world_tree();
+        world.add_unique(HierarchyEvents::<MyTree>::new());
+
+        let x = world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            let x = hierarchy.entities.add_entity((), ());
+            hierarchy.set_children(a, &[x, g, e, x]);
+
+            assert!(hierarchy.children(a).eq([x, g, e]));
+            assert!(hierarchy.children(c).eq([f]));
+            // d is a root now, with its subtree intact
+            assert!(hierarchy.ancestors(d).eq(None));
+            assert!(hierarchy.children(d).eq([h, i]));
+            x
+        });
+
+        world.run(|mut events: UniqueViewMut<HierarchyEvents<MyTree>>| {
+            assert!(events.drain().eq([
+                HierarchyEvent::Detached {
+                    id: d,
+                    old_parent: a
+                },
+                HierarchyEvent::Attached {
+                    id: x,
+                    parent: a,
+                    index: 1
+                },
+                HierarchyEvent::Detached {
+                    id: g,
+                    old_parent: c
+                },
+                HierarchyEvent::Attached {
+                    id: g,
+                    parent: a,
+                    index: 2
+                },
+                HierarchyEvent::Reordered { parent: a },
+            ]));
+        });
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            // already the case
+            hierarchy.set_children(a, &[x, g, e]);
+            hierarchy.set_children(b, &[]);
+            // a and its parent can't be children of a, they're skipped
+            hierarchy.set_children(a, &[root, x, a, g, e]);
+            assert!(hierarchy.children(a).eq([x, g, e]));
+            assert!(hierarchy.ancestors(a).eq([root]));
+            assert!(hierarchy.events.as_ref().unwrap().is_empty());
+
+            hierarchy.set_children(root, &[c]);
+            assert!(hierarchy.children(root).eq([c]));
+            hierarchy.set_children(a, &[]);
+            assert!(hierarchy.children(a).eq(None));
+            assert!(!hierarchy.parent_storage.contains(a));
+            assert!(!hierarchy.child_storage.contains(x));
+        });
+    }
+
+    // every child points back at the parent, and the ring holds together both ways
+    fn check_ring(hierarchy: &HierarchyViewMut<MyTree>, parent: EntityId) {
+        let children = hierarchy.children(parent).collect::<Vec<_>>();
+        assert_eq!(
+            hierarchy.parent_storage[parent].num_children,
+            children.len()
+        );
+        for (index, id) in children.iter().enumerate() {
+            let child = hierarchy.child_storage[*id];
+            assert_eq!(child.parent, parent);
+            assert_eq!(child.next, children[(index + 1) % children.len()]);
+            assert_eq!(
+                child.prev,
+                children[(index + children.len() - 1) % children.len()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_attach_many() {
+        let (world, (root, a, b, c, d, e, f, g, ..), _) = create_world_tree();
+        world.add_unique(HierarchyEvents::<MyTree>::new());
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            let new = hierarchy.attach_new_many(b, 3);
+            assert!(hierarchy.children(b).eq(new.iter().cloned()));
+            check_ring(&hierarchy, b);
+
+            // some of them come from elsewhere, a is already a child and moves to the end
+            hierarchy.attach_many(root, &[f, a, d]);
+            assert!(hierarchy.children(root).eq([b, c, f, a, d]));
+            assert!(hierarchy.children(c).eq([g]));
+            assert!(hierarchy.children(a).eq([e]));
+            check_ring(&hierarchy, root);
+            check_ring(&hierarchy, a);
+
+            // the only child leaving and coming back
+            hierarchy.attach_many(a, &[e]);
+            assert!(hierarchy.children(a).eq([e]));
+            check_ring(&hierarchy, a);
+
+            hierarchy.attach_many(root, &[]);
+            assert!(hierarchy.children(root).eq([b, c, f, a, d]));
+
+            let events = hierarchy.events.as_mut().unwrap();
+            assert!(events.drain().skip(3).eq([
+                HierarchyEvent::Detached {
+                    id: f,
+                    old_parent: c
+                },
+                HierarchyEvent::Detached {
+                    id: a,
+                    old_parent: root
+                },
+                HierarchyEvent::Detached {
+                    id: d,
+                    old_parent: a
+                },
+                HierarchyEvent::Attached {
+                    id: f,
+                    parent: root,
+                    index: 2
+                },
+                HierarchyEvent::Attached {
+                    id: a,
+                    parent: root,
+                    index: 3
+                },
+                HierarchyEvent::Attached {
+                    id: d,
+                    parent: root,
+                    index: 4
+                },
+                HierarchyEvent::Detached {
+                    id: e,
+                    old_parent: a
+                },
+                HierarchyEvent::Attached {
+                    id: e,
+                    parent: a,
+                    index: 0
+                },
+            ]));
+
+            // repeated ids are attached once, at their first place
+            hierarchy.attach_many(b, &[g, c, g, c]);
+            assert!(hierarchy.children(b).eq(new.iter().cloned().chain([g, c])));
+            assert!(hierarchy.children(root).eq([b, f, a, d]));
+            check_ring(&hierarchy, b);
+            check_ring(&hierarchy, root);
+
+            // b and its parent can't be children of b, they're skipped
+            hierarchy.attach_many(b, &[b, root]);
+            assert!(hierarchy.children(b).eq(new.iter().cloned().chain([g, c])));
+            assert!(hierarchy.ancestors(b).eq([root]));
+            hierarchy.attach_many(b, &[root, d, b]);
+            assert!(hierarchy
+                .children(b)
+                .eq(new.iter().cloned().chain([g, c, d])));
+            check_ring(&hierarchy, b);
+            check_ring(&hierarchy, root);
+        });
+    }
+
+    #[test]
+    fn test_detach_and_child_policies() {
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, _, m, _), _) = create_world_tree();
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            hierarchy.detach(d);
+            assert!(hierarchy.children(a).eq([e]));
+            assert!(hierarchy.ancestors(d).eq(None));
+            assert!(hierarchy.children(d).eq([h, i]));
+            // already a root
+            hierarchy.detach(d);
+            assert!(hierarchy.ancestors(d).eq(None));
+            hierarchy.attach_at(d, a, 0);
+
+            // g's children take its place under c
+            hierarchy.attach(b, c);
+            assert!(hierarchy.remove_single_with(g, ChildPolicy::ReparentToGrandparent));
+            assert!(hierarchy.children(c).eq([f, j, k, b]));
+            assert!(hierarchy.ancestors(g).eq(None));
+            assert!(hierarchy.children(g).eq(None));
+
+            // the children can't go to c itself or below it
+            assert!(!hierarchy.remove_single_with(c, ChildPolicy::ReparentTo(c)));
+            assert!(!hierarchy.remove_single_with(c, ChildPolicy::ReparentTo(m)));
+            assert!(hierarchy.children(c).eq([f, j, k, b]));
+            assert!(hierarchy.ancestors(m).eq([j, c, root]));
+
+            assert!(hierarchy.remove_single_with(c, ChildPolicy::ReparentTo(d)));
+            assert!(hierarchy.children(d).eq([h, i, f, j, k, b]));
+            assert!(hierarchy.children(root).eq([a]));
+
+            assert!(hierarchy.remove_single_with(d, ChildPolicy::Orphan));
+            assert!(hierarchy.children(a).eq([e]));
+            for id in [h, i, f, j, k, b] {
+                assert!(hierarchy.ancestors(id).eq(None));
+            }
+
+            // no grandparent, the children become roots
+            assert!(hierarchy.remove_single_with(root, ChildPolicy::ReparentToGrandparent));
+            assert!(hierarchy.ancestors(a).eq(None));
+            assert!(hierarchy.children(a).eq([e]));
+        });
+    }
+
+    #[test]
+    fn test_ungroup_and_group() {
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, _, m, _), _) = create_world_tree();
+        world.add_unique(HierarchyEvents::<MyTree>::new());
+
+        world.run(|mut hierarchy: HierarchyViewMut<MyTree>| {
+            hierarchy.ungroup(a);
+            assert!(hierarchy.children(root).eq([d, e, b, c]));
+            assert!(hierarchy.ancestors(a).eq(None));
+            assert!(hierarchy.children(a).eq(None));
+            assert!(hierarchy.children(d).eq([h, i]));
+            check_ring(&hierarchy, root);
+
+            // back the way it was
+            assert!(hierarchy.group(&[d, e], a));
+            assert!(hierarchy.children(root).eq([a, b, c]));
+            assert!(hierarchy.children(a).eq([d, e]));
+            check_ring(&hierarchy, root);
+            check_ring(&hierarchy, a);
+
+            let events = hierarchy.events.as_mut().unwrap();
+            assert!(events.drain().eq([
+                HierarchyEvent::Detached {
+                    id: a,
+                    old_parent: root
+                },
+                HierarchyEvent::Detached {
+                    id: d,
+                    old_parent: a
+                },
+                HierarchyEvent::Attached {
+                    id: d,
+                    parent: root,
+                    index: 0
+                },
+                HierarchyEvent::Detached {
+                    id: e,
+                    old_parent: a
+                },
+                HierarchyEvent::Attached {
+                    id: e,
+                    parent: root,
+                    index: 1
+                },
+                HierarchyEvent::Attached {
+                    id: a,
+                    parent: root,
+                    index: 0
+                },
+                HierarchyEvent::Detached {
+                    id: d,
+                    old_parent: root
+                },
+                HierarchyEvent::Attached {
+                    id: d,
+                    parent: a,
+                    index: 0
+                },
+                HierarchyEvent::Detached {
+                    id: e,
+                    old_parent: root
+                },
+                HierarchyEvent::Attached {
+                    id: e,
+                    parent: a,
+                    index: 1
+                },
+            ]));
+
+            // only child, in the middle, at the end
+            hierarchy.ungroup(j);
+            assert!(hierarchy.children(g).eq([m, k]));
+            hierarchy.ungroup(d);
+            assert!(hierarchy.children(a).eq([h, i, e]));
+            check_ring(&hierarchy, a);
+            hierarchy.ungroup(c);
+            assert!(hierarchy.children(root).eq([a, b, f, g]));
+            check_ring(&hierarchy, root);
+
+            // not a run: not siblings, out of order, wrapping around, cycles
+            assert!(!hierarchy.group(&[b, h], d));
+            assert!(!hierarchy.group(&[f, b], d));
+            assert!(!hierarchy.group(&[g, a], d));
+            assert!(!hierarchy.group(&[a, b], h));
+            assert!(!hierarchy.group(&[a], a));
+            assert!(!hierarchy.group(&[], d));
+
+            // nor can the run go into its own parent or grandparent
+            assert!(!hierarchy.group(&[h, i], a));
+            assert!(!hierarchy.group(&[h, i], root));
+            assert!(hierarchy.children(a).eq([h, i, e]));
+            assert!(hierarchy.children(root).eq([a, b, f, g]));
+            check_ring(&hierarchy, a);
+            check_ring(&hierarchy, root);
+
+            // a sibling of the run takes its place, a former parent keeps its children
+            assert!(hierarchy.group(&[b, f], a));
+            assert!(hierarchy.children(root).eq([a, g]));
+            assert!(hierarchy.children(a).eq([h, i, e, b, f]));
+            check_ring(&hierarchy, root);
+            check_ring(&hierarchy, a);
+
+            // the whole child list
+            assert!(hierarchy.group(&[a, g], c));
+            assert!(hierarchy.children(root).eq([c]));
+            assert!(hierarchy.children(c).eq([a, g]));
+            check_ring(&hierarchy, root);
+            check_ring(&hierarchy, c);
+
+            // a root ungrouped leaves roots behind
+            hierarchy.ungroup(root);
+            assert!(hierarchy.ancestors(c).eq(None));
+            assert!(!hierarchy.parent_storage.contains(root));
+        });
+    }
+
+    #[test]
+    fn test_links() {
+        let mut world = World::new();
+        let [shader, texture, material_a, material_b, mesh] = [(); 5].map(|_| world.add_entity(()));
+
+        world.run(|mut links: LinksViewMut<MyTree>| {
+            links.link(mesh, material_a).unwrap();
+            links.link(mesh, material_b).unwrap();
+            links.link(material_a, shader).unwrap();
+            links.link(material_b, shader).unwrap();
+            links.link(material_b, texture).unwrap();
+            // linking twice is a no-op
+            links.link(mesh, material_a).unwrap();
+
+            assert!(links.links(mesh).eq([material_a, material_b]));
+            assert!(links.backlinks(shader).eq([material_a, material_b]));
+            // shader is shared but only visited once
+            assert!(links
+                .reachable(mesh)
+                .eq([material_a, shader, material_b, texture]));
+            assert!(links.reaches(mesh, texture));
+            assert!(!links.reaches(texture, mesh));
+
+            assert_eq!(
+                links.link(shader, mesh),
+                Err(LinkError::Cycle {
+                    from: shader,
+                    to: mesh
+                })
+            );
+            assert!(links.link(mesh, mesh).is_err());
+            assert!(links.links(shader).eq(None));
+
+            assert!(links.unlink(material_b, shader));
+            assert!(!links.unlink(material_b, shader));
+            assert!(links.backlinks(shader).eq([material_a]));
+
+            links.unlink_all(material_b);
+            assert!(links.links(mesh).eq([material_a]));
+            assert!(!links.link_storage.contains(material_b));
+            assert!(!links.backlink_storage.contains(material_b));
+            assert!(!links.backlink_storage.contains(texture));
+        });
+
+        // the tree of the same tag is untouched
+        world.run(|hierarchy: HierarchyView<MyTree>| {
+            assert!(hierarchy.child_storage.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_edges() {
+        let world = World::new();
+
+        world.run(|mut edges: HierarchyEdgesViewMut<MyTree, &'static str>| {
+            let root = edges.hierarchy.entities.add_entity((), ());
+            let arm = edges.attach_new_with(root, "shoulder");
+            let hand = edges.attach_new_with(arm, "wrist");
+            let other = edges.attach_new(root);
+            assert_eq!(edges.edge(arm), Some(&"shoulder"));
+            assert_eq!(edges.edge(other), None);
+
+            // reordering under the same parent keeps the link
+            edges.attach_at(arm, root, 1);
+            edges.sort_children_by(root, |a, b| b.cmp(a));
+            assert_eq!(edges.edge(arm), Some(&"shoulder"));
+            *edges.edge_mut(arm).unwrap() = "hip";
+            assert_eq!(edges.edge(arm), Some(&"hip"));
+
+            // a new parent is a new link
+            edges.attach(hand, other);
+            assert_eq!(edges.edge(hand), None);
+            edges.attach_with(hand, arm, "wrist");
+            assert_eq!(edges.edge(hand), Some(&"wrist"));
+
+            // a refused removal keeps every link
+            assert!(!edges.remove_single_with(arm, ChildPolicy::ReparentTo(hand)));
+            assert_eq!(edges.edge(arm), Some(&"hip"));
+            assert_eq!(edges.edge(hand), Some(&"wrist"));
+            // and so do skipped children
+            edges.set_children(hand, &[arm, hand]);
+            assert_eq!(edges.edge(arm), Some(&"hip"));
+            assert_eq!(edges.edge(hand), Some(&"wrist"));
+
+            // the children of a removed node are detached, their links are gone too
+            edges.remove_single(arm);
+            assert!(edges.edge_storage.is_empty());
+
+            edges.attach_with(arm, other, "shoulder");
+            edges.attach_with(hand, arm, "wrist");
+            edges.remove(other);
+            assert!(edges.edge_storage.is_empty());
+        });
+    }
+
     #[test]
     fn test_sorting_depth_first() {
         #[derive(PartialEq, Eq, Debug, PartialOrd, Ord)]
-        struct USIZE(usize);
-        impl Component for USIZE {
+        struct Usize(usize);
+        impl Component for Usize {
             type Tracking = track::Untracked;
         }
 
         let world = World::new();
 
         let (mut hierarchy, mut usizes) = world
-            .borrow::<(
-                (
-                    EntitiesViewMut,
-                    ViewMut<Parent<MyTree>>,
-                    ViewMut<Child<MyTree>>,
-                ),
-                ViewMut<USIZE>,
-            )>()
+            .borrow::<(HierarchyViewMut<MyTree>, ViewMut<Usize>)>()
             .unwrap();
 
-        let mut hierarchy = (&mut hierarchy.0, &mut hierarchy.1, &mut hierarchy.2);
-
         let root = {
-            let entities = &mut hierarchy.0;
+            let entities = &mut hierarchy.entities;
             entities.add_entity((), ())
         };
 
@@ -101,16 +1295,16 @@ mod tests {
         let e4 = hierarchy.attach_new(root);
 
         {
-            let entities = &mut hierarchy.0;
-            entities.add_component(e0, &mut usizes, USIZE(7));
-            entities.add_component(e1, &mut usizes, USIZE(5));
-            entities.add_component(e2, &mut usizes, USIZE(6));
-            entities.add_component(e3, &mut usizes, USIZE(1));
-            entities.add_component(e4, &mut usizes, USIZE(3));
+            let entities = &mut hierarchy.entities;
+            entities.add_component(e0, &mut usizes, Usize(7));
+            entities.add_component(e1, &mut usizes, Usize(5));
+            entities.add_component(e2, &mut usizes, Usize(6));
+            entities.add_component(e3, &mut usizes, Usize(1));
+            entities.add_component(e4, &mut usizes, Usize(3));
         }
 
         {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
+            let storages = &hierarchy;
             assert!(storages
                 .children(root)
                 .eq([e0, e1, e2, e3, e4].iter().cloned()));
@@ -119,7 +1313,7 @@ mod tests {
         hierarchy.sort_children_by(root, |a, b| usizes[*a].cmp(&usizes[*b]));
 
         {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
+            let storages = &hierarchy;
             assert!(storages
                 .children(root)
                 .eq([e3, e4, e1, e2, e0].iter().cloned()));
@@ -167,16 +1361,8 @@ mod tests {
         let mut labels = HashMap::<EntityId, &'static str>::new();
 
         let entities = {
-            let mut hierarchy = world
-                .borrow::<(
-                    EntitiesViewMut,
-                    ViewMut<Parent<MyTree>>,
-                    ViewMut<Child<MyTree>>,
-                )>()
-                .unwrap();
-
-            let mut hierarchy = (&mut hierarchy.0, &mut hierarchy.1, &mut hierarchy.2);
-            let entities = &mut hierarchy.0;
+            let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
+            let entities = &mut hierarchy.entities;
 
             let root = entities.add_entity((), ());
 
@@ -222,11 +1408,8 @@ mod tests {
         let (world, (root, a, b, c, d, e, f, g, h, i, j, k, l, m, n), _) = create_world_tree();
 
         {
-            let (parent_storage, child_storage) = world
-                .borrow::<(View<Parent<MyTree>>, View<Child<MyTree>>)>()
-                .unwrap();
+            let storages = world.borrow::<HierarchyView<MyTree>>().unwrap();
 
-            let storages = (&parent_storage, &child_storage);
             assert!(storages.descendants_depth_first(root).eq([
                 a, d, h, l, i, e, b, c, f, g, j, m, n, k
             ]
@@ -237,23 +1420,134 @@ mod tests {
             ]
             .iter()
             .cloned()));
+            assert!(storages.descendants_post_order(root).eq([
+                l, h, i, d, e, a, b, f, n, m, j, k, g, c
+            ]
+            .iter()
+            .cloned()));
+            assert!(storages.descendants_post_order(l).eq(None));
         }
     }
 
     #[test]
-    fn test_debug_print() {
-        let world = World::new();
+    fn test_component_and_iter_traits() {
+        let (world, (root, a, b, c, d, ..), _) = create_world_tree();
 
-        let mut hierarchy = world
-            .borrow::<(
-                EntitiesViewMut,
-                ViewMut<Parent<MyTree>>,
-                ViewMut<Child<MyTree>>,
-            )>()
+        let (parent_storage, child_storage) = world
+            .borrow::<(View<Parent<MyTree>>, View<Child<MyTree>>)>()
             .unwrap();
 
-        let mut hierarchy = (&mut hierarchy.0, &mut hierarchy.1, &mut hierarchy.2);
-        let entities = &mut hierarchy.0;
+        // MyTree implements none of these, the components still do
+        let child = child_storage[a];
+        assert_eq!(child, Child::<MyTree>::new(root, c, b));
+        assert_ne!(child, child_storage[b]);
+        assert_eq!(parent_storage[root], Parent::<MyTree>::new(3, a));
+        assert_eq!(
+            format!("{:?}", parent_storage[a]),
+            format!("Parent {{ num_children: 2, first_child: {:?} }}", d)
+        );
+
+        let storages = (&parent_storage, &child_storage);
+        let mut iter = storages.descendants_depth_first(root);
+        iter.next();
+        let fork = iter.clone();
+        assert!(iter.eq(fork));
+        assert!(!format!("{:?}", storages.children(root)).is_empty());
+    }
+
+    #[test]
+    fn test_commands_undo_redo() {
+        let (world, (root, a, b, c, d, e, f, g, h, i, j, k, l, m, n), _) = create_world_tree();
+        let all = [root, a, b, c, d, e, f, g, h, i, j, k, l, m, n];
+
+        let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
+
+        // every link, so undo has to restore the exact sibling positions
+        let snapshot = |hierarchy: &HierarchyViewMut<MyTree>| {
+            all.iter()
+                .map(|id| {
+                    (
+                        hierarchy.parent_storage.get(*id).ok().copied(),
+                        hierarchy.child_storage.get(*id).ok().copied(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let commands = vec![
+            HierarchyCommand::Move {
+                id: e,
+                parent: c,
+                index: 1,
+            },
+            HierarchyCommand::Detach { id: h },
+            HierarchyCommand::Reorder {
+                parent: root,
+                children: vec![c, b, a],
+            },
+            HierarchyCommand::Remove { root: g },
+            HierarchyCommand::Attach { id: d, parent: b },
+            HierarchyCommand::Detach { id: h },
+            HierarchyCommand::Move {
+                id: i,
+                parent: root,
+                index: 0,
+            },
+        ];
+
+        let before = snapshot(&hierarchy);
+
+        let mut undo = Vec::new();
+        for command in &commands {
+            undo.push(command.apply(&mut hierarchy));
+        }
+
+        let after = snapshot(&hierarchy);
+        assert!(hierarchy.children(c).eq([f, e].iter().cloned()));
+        assert!(hierarchy.children(root).eq([i, c, b, a].iter().cloned()));
+        assert!(hierarchy.ancestors(h).eq(None));
+        assert!(hierarchy.descendants_depth_first(g).eq(None));
+
+        let mut redo = Vec::new();
+        while let Some(command) = undo.pop() {
+            redo.push(command.apply(&mut hierarchy));
+        }
+        assert_eq!(before, snapshot(&hierarchy));
+
+        while let Some(command) = redo.pop() {
+            command.apply(&mut hierarchy);
+        }
+        assert_eq!(after, snapshot(&hierarchy));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_iter() {
+        use rayon::prelude::*;
+
+        let (world, (root, a, b, c, ..), _) = create_world_tree();
+        let hierarchy = world.borrow::<HierarchyView<MyTree>>().unwrap();
+
+        let mut children = hierarchy.par_children(root).collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(children, vec![a, b, c]);
+
+        for id in [root, a, c] {
+            let mut expected = hierarchy.descendants_depth_first(id).collect::<Vec<_>>();
+            let mut descendants = hierarchy.par_descendants(id).collect::<Vec<_>>();
+            expected.sort();
+            descendants.sort();
+            assert_eq!(expected, descendants);
+        }
+        assert_eq!(hierarchy.par_descendants(b).count(), 0);
+    }
+
+    #[test]
+    fn test_debug_print() {
+        let world = World::new();
+
+        let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
+        let entities = &mut hierarchy.entities;
 
         let root = entities.add_entity((), ());
 
@@ -268,13 +1562,10 @@ mod tests {
         let _e7 = hierarchy.attach_new(e5);
         let _e8 = hierarchy.attach_new(e6);
 
-        {
-            let storages = (&*hierarchy.1, &*hierarchy.2);
-            assert_eq!(
-                EXPECTED_DEBUG_TREE_1,
-                format!("{:?}", storages.debug_tree(root, |e| format!("{:?}", e)))
-            );
-        }
+        assert_eq!(
+            EXPECTED_DEBUG_TREE_1,
+            format!("{:?}", hierarchy.debug_tree(root, |e| format!("{:?}", e)))
+        );
 
         let (world, entities, labels) = create_world_tree();
 
@@ -298,8 +1589,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dot_print() {
+        let world = World::new();
+
+        let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
+        let entities = &mut hierarchy.entities;
+
+        let root = entities.add_entity((), ());
+
+        let e1 = hierarchy.attach_new(root);
+        let _e2 = hierarchy.attach_new(root);
+        let _e3 = hierarchy.attach_new(e1);
+
+        let mut labels = HashMap::<EntityId, &'static str>::new();
+        labels.insert(root, "root");
+        labels.insert(e1, "\"quoted\"");
+
+        let get_label = |e| {
+            labels
+                .get(&e)
+                .map_or_else(|| "node".to_string(), |l| l.to_string())
+        };
+
+        assert_eq!(
+            EXPECTED_DOT_TREE,
+            hierarchy.dot_tree(root, get_label).to_string()
+        );
+        assert_eq!(
+            EXPECTED_DOT_TREE_SIBLINGS,
+            hierarchy
+                .dot_tree(root, get_label)
+                .with_sibling_edges(true)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_display_print() {
+        let (world, entities, labels) = create_world_tree();
+
+        let hierarchy = world.borrow::<HierarchyView<MyTree>>().unwrap();
+        let get_label = |e| labels.get(&e).unwrap().to_string();
+
+        assert_eq!(
+            EXPECTED_DISPLAY_TREE,
+            hierarchy.display_tree(entities.0, get_label).to_string()
+        );
+        assert_eq!(
+            EXPECTED_DISPLAY_TREE_ASCII_DEPTH_2,
+            hierarchy
+                .display_tree(entities.0, get_label)
+                .ascii(true)
+                .max_depth(2)
+                .to_string()
+        );
+        assert_eq!(
+            EXPECTED_DISPLAY_TREE_IDS_DEPTH_0,
+            hierarchy
+                .display_tree(entities.0, get_label)
+                .show_ids(true)
+                .max_depth(0)
+                .to_string()
+        );
+        assert_eq!(
+            "d\n├── h\n│   └── l\n└── i\n",
+            hierarchy.display_tree(entities.4, get_label).to_string()
+        );
+    }
+
+    // tiny deterministic LCG so the random trees are reproducible without extra dependencies
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound
+        }
+    }
+
+    fn reference_debug_tree(
+        storages: (&View<Parent<MyTree>>, &View<Child<MyTree>>),
+        entity_id: EntityId,
+        depth: usize,
+        out: &mut String,
+    ) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&format!("{}\n", entity_id.index()));
+        for child in storages.children(entity_id) {
+            reference_debug_tree(storages, child, depth + 1, out);
+        }
+    }
+
+    #[test]
+    fn test_debug_print_random_trees() {
+        let mut rng = Lcg(0x5eed);
+
+        for _ in 0..200 {
+            let world = World::new();
+            let size = 1 + rng.next(40);
+
+            let root = {
+                let mut hierarchy = world.borrow::<HierarchyViewMut<MyTree>>().unwrap();
+                let root = hierarchy.entities.add_entity((), ());
+
+                let mut nodes = vec![root];
+                for _ in 0..size {
+                    let parent = nodes[rng.next(nodes.len())];
+                    nodes.push(hierarchy.attach_new(parent));
+                }
+
+                // shuffle things around a bit, skipping moves that would create a cycle
+                for _ in 0..rng.next(10) {
+                    let id = nodes[1 + rng.next(nodes.len() - 1)];
+                    let parent = nodes[rng.next(nodes.len())];
+                    let storages = &hierarchy;
+                    if parent != id && !storages.ancestors(parent).any(|e| e == id) {
+                        hierarchy.attach(id, parent);
+                    }
+                }
+                root
+            };
+
+            let (parent_storage, child_storage) = world
+                .borrow::<(View<Parent<MyTree>>, View<Child<MyTree>>)>()
+                .unwrap();
+            let storages = (&parent_storage, &child_storage);
+
+            let mut expected = String::new();
+            reference_debug_tree(storages, root, 0, &mut expected);
+
+            assert_eq!(
+                expected,
+                format!("{:?}", storages.debug_tree(root, |e| e.index().to_string()))
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_tree_value() {
+        let (world, entities, labels) = create_world_tree();
+
+        let hierarchy = world.borrow::<HierarchyView<MyTree>>().unwrap();
+        let get_label = |e| labels.get(&e).unwrap().to_string();
+
+        let value = hierarchy.to_tree_value(entities.4, get_label);
+
+        assert_eq!(value.id, entities.4);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"id":{"index":4,"gen":0},"label":"d","children":[{"id":{"index":8,"gen":0},"label":"h","children":[{"id":{"index":10,"gen":0},"label":"l","children":[]}]},{"id":{"index":9,"gen":0},"label":"i","children":[]}]}"#
+        );
+
+        let value = hierarchy.to_tree_value(entities.0, get_label);
+        let ron = ron::to_string(&value).unwrap();
+        assert_eq!(value, ron::from_str::<TreeValue>(&ron).unwrap());
+    }
+
     // TODO: Consider future proofing the expected syntax here as EntityId's Debug syntax has changed and may change again.
-    const EXPECTED_DEBUG_TREE_1: &'static str = r#"EId(0.0)
+    const EXPECTED_DEBUG_TREE_1: &str = r#"EId(0.0)
   EId(1.0)
     EId(3.0)
     EId(4.0)
@@ -310,7 +1762,7 @@ mod tests {
       EId(8.0)
 "#;
 
-    const EXPECTED_DEBUG_TREE_2: &'static str = r#"root
+    const EXPECTED_DEBUG_TREE_2: &str = r#"root
   a
     d
       h
@@ -325,5 +1777,61 @@ mod tests {
         m
           n
       k
+"#;
+
+    const EXPECTED_DOT_TREE: &str = r#"digraph hierarchy {
+    e0_0 [label="root"];
+    e1_0 [label="\"quoted\""];
+    e0_0 -> e1_0;
+    e3_0 [label="node"];
+    e1_0 -> e3_0;
+    e2_0 [label="node"];
+    e0_0 -> e2_0;
+}
+"#;
+
+    const EXPECTED_DOT_TREE_SIBLINGS: &str = r#"digraph hierarchy {
+    e0_0 [label="root"];
+    e1_0 [label="\"quoted\""];
+    e0_0 -> e1_0;
+    e1_0 -> e2_0 [style=dashed, constraint=false];
+    e3_0 [label="node"];
+    e1_0 -> e3_0;
+    e2_0 [label="node"];
+    e0_0 -> e2_0;
+}
+"#;
+
+    const EXPECTED_DISPLAY_TREE: &str = r#"root
+├── a
+│   ├── d
+│   │   ├── h
+│   │   │   └── l
+│   │   └── i
+│   └── e
+├── b
+└── c
+    ├── f
+    └── g
+        ├── j
+        │   └── m
+        │       └── n
+        └── k
+"#;
+
+    const EXPECTED_DISPLAY_TREE_ASCII_DEPTH_2: &str = r#"root
+|-- a
+|   |-- d
+|   |   `-- ... 3 more
+|   `-- e
+|-- b
+`-- c
+    |-- f
+    `-- g
+        `-- ... 4 more
+"#;
+
+    const EXPECTED_DISPLAY_TREE_IDS_DEPTH_0: &str = r#"root (0.0)
+└── … 14 more
 "#;
 }